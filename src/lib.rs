@@ -3,7 +3,18 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod libs;
+pub use libs::animation::Easing;
 pub use libs::app::TemplateApp;
+pub use libs::board::{Board, Difficulty};
+pub use libs::cell::{Direction, Move, TaquinSide, TaquinCoord, MIN_SIDE, MAX_SIDE};
+pub use libs::history::History;
+pub use libs::notation::{parse_moves, format_moves, ParseMovesError};
+pub use libs::position::ParsePositionError;
+pub use libs::reduction::Reduction;
+pub use libs::save::{SavedGame, SavedCoord, LoadedGame, LoadError};
+pub use libs::slicing::SliceMode;
+pub use libs::solver::Solver;
+pub use libs::solving::{solve, Solution, Solving};
 
 // ----------------------------------------------------------------------------
 // When compiling for web:
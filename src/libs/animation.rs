@@ -0,0 +1,82 @@
+use super::cell::Direction;
+
+/// How a tile speeds up and slows down along its slide.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Easing{
+    Linear,
+    /// Fast at first, slowing down into place.
+    EaseOut,
+    /// Slow at both ends.
+    EaseInOut,
+}
+
+impl Easing{
+    pub const ALL: [Easing; 3] = [
+        Easing::Linear,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    /// The part of the way gone at `t`, the part of the time gone, both from 0 to 1.
+    pub fn apply(&self, t: f32) -> f32{
+        let t = t.clamp(0.0, 1.0);
+        match self{
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// The tiles shifted by one move, sliding from where they were to where they are.
+///
+/// The board is moved at once, the slide only tells how far from its place
+/// each tile is to be drawn.
+#[derive(Debug, Clone)]
+pub struct Slide{
+    // the ranks of the tiles moving
+    ranks: Vec<usize>,
+    // the way the void cell went, the tiles come from there
+    direction: Direction,
+    // seconds
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+
+impl Slide{
+    pub fn new(ranks: Vec<usize>, direction: Direction, duration: f32, easing: Easing) -> Self{
+        Self{
+            ranks,
+            direction,
+            elapsed: 0.0,
+            duration,
+            easing,
+        }
+    }
+
+    /// Lets the time go by `dt` seconds.
+    pub fn tick(&mut self, dt: f32){
+        self.elapsed += dt;
+    }
+
+    pub fn is_finished(&self) -> bool{
+        self.elapsed >= self.duration
+    }
+
+    /// How far the tile of `rank` is from its place, in cells
+    /// along the columns and the lines: (0, 0) for a tile not moving.
+    pub fn offset(&self, rank: usize) -> (f32, f32){
+        if self.is_finished() || ! self.ranks.contains(&rank){
+            return (0.0, 0.0);
+        }
+        let left = 1.0 - self.easing.apply(self.elapsed / self.duration);
+        match self.direction{
+            Direction::Left => (-left, 0.0),
+            Direction::Right => (left, 0.0),
+            Direction::Up => (0.0, -left),
+            Direction::Down => (0.0, left),
+            Direction::Dontapply => (0.0, 0.0),
+        }
+    }
+}
@@ -11,40 +11,212 @@ use super::cell::{
     TaquinSide, 
     TaquinCoord,
     Direction,
+    Move,
+    MIN_SIDE,
+    MAX_SIDE,
 };
+use super::board::{Board, Difficulty};
+use super::history::History;
+use super::solving::{Solution, Solving};
+use super::playback::Playback;
+use super::score::Score;
+use super::position::ParsePositionError;
+use super::notation::{parse_moves, format_moves, moves_from_directions};
+use super::save::{SavedGame, SavedCoord, LoadError, SAVE_VERSION, image_hash};
+use super::slicing::SliceMode;
+use super::animation::{Easing, Slide};
 
-use std::{path::Path,};
-use image::{GenericImageView,};
-use std::collections::HashMap;
+use std::collections::VecDeque;
+use image::{DynamicImage, RgbaImage,};
 use rand::{thread_rng, Rng,};
 use log::{
-    debug, 
+    // debug, 
     // error, 
     info, 
+    warn,
     // log_enabled, 
     // Level,
 };
 
-const BACKGROUND_IMAGE_PATH: &'static str = "./image/background.png";
-const VOID_CELL_CURRENT_IMAGE_PATH: &'static str = "./image/void_cell_current.png";
-const VOID_CELL_WINNER_IMAGE_PATH: &'static str = "./image/void_cell_winner.png";
+// the default images, a file at the path replaces the one built in
+// (native only, there is no file system in a browser)
+const BACKGROUND_IMAGE_PATH: &str = "./image/background.png";
+const VOID_CELL_CURRENT_IMAGE_PATH: &str = "./image/void_cell_current.png";
+const VOID_CELL_WINNER_IMAGE_PATH: &str = "./image/void_cell_winner.png";
+
+const BACKGROUND_IMAGE: &[u8] = include_bytes!("../../image/background.png");
+const VOID_CELL_CURRENT_IMAGE: &[u8] = include_bytes!("../../image/void_cell_current.png");
+const VOID_CELL_WINNER_IMAGE: &[u8] = include_bytes!("../../image/void_cell_winner.png");
+
+// the optimal hint gives up above this number of positions explored,
+// a longer solution is hinted then
+const HINT_MAX_NODES: u64 = 2_000_000;
+
+// the optimal auto-solve gives up above this number of positions explored,
+// a longer solution is played then
+const AUTO_SOLVE_MAX_NODES: u64 = 5_000_000;
+
+// the moves of a longer auto-solve are not listed, they would not fit
+const AUTO_SOLVE_MAX_LISTED: usize = 200;
+
+// the file proposed to save a game
+const SAVE_FILE_PATH: &str = "./taquin_save.json";
+
+// smaller, an image shows nothing; it needs not be as large as the grid
+// in pixels, `SliceMode::fit` scales it up when it is not
+const MIN_IMAGE_SIDE: u32 = 8;
+
+// smaller, an image tile is drawn without the frame of a button,
+// the frame would hide most of it
+const FRAMED_TILE_MIN_SIDE: f32 = 32.0;
+
+// what the player asks for, run at once unless tiles are sliding
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlayerInput{
+    Click(TaquinCoord),
+    // the way the arrow points
+    Key(Direction),
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileAction{
+    Save,
+    Open,
+    OpenImage,
+}
+
+// what a search of the solution is run for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SolveFor{
+    Hint,
+    AutoSolve,
+}
+
+// a search of the solution running aside, dropped when the board
+// is no longer the one it started from
+struct PendingSolve{
+    purpose: SolveFor,
+    ranks: Vec<usize>,
+    solving: Solving,
+}
+
+/// The logical state of the game and the settings, persisted on shutdown.
+/// The images are not, they are sliced anew on startup.
+#[cfg(feature = "persistence")]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+struct PersistedState{
+    num_col: usize,
+    num_line: usize,
+    // the ranks line by line, the greatest one is the void cell
+    cells: Vec<usize>,
+    void_col: usize,
+    void_line: usize,
+    scrambled: bool,
+    seed: Option<u64>,
+    scramble_level: Option<Difficulty>,
+    side_panel_show: bool,
+    trace: bool,
+    move_hole: bool,
+    playback_speed: f32,
+    slice_mode: SliceMode,
+    padding: [u8; 4],
+    numbered: bool,
+    rank_overlay: bool,
+    rank_font_size: f32,
+    rank_contrast: f32,
+    reduced_motion: bool,
+    slide_duration: f32,
+    easing: Easing,
+}
+
+#[cfg(feature = "persistence")]
+impl Default for PersistedState{
+    fn default() -> Self{
+        let app = TemplateApp::default();
+        app.persisted_state()
+    }
+}
+
+// the images on the GPU, uploaded once after each slicing
+struct Textures{
+    // the image fitted to the grid, each cell shows a part of it
+    image: egui::TextureHandle,
+    void_cell: egui::TextureHandle,
+    winner: egui::TextureHandle,
+}
 
-/// We derive Deserialize/Serialize so we can persist app state on shutdown.
-#[cfg_attr(feature = "persistence", derive(serde::Deserialize, serde::Serialize))]
-#[cfg_attr(feature = "persistence", serde(default))] // if we add new fields, give them default values when deserializing old state
 pub struct TemplateApp {
     label: String,
-    cells_map: HashMap<String, Cell,>,
-    sides: TaquinSide,
+    board: Board,
+    history: History,
+    score: Score,
+    summary_show: bool,
+    // the image fitted to the grid, as sliced
     image: ColorImage,
+    // how the image is fitted to the grid, and the color of the letterbox
+    slice_mode: SliceMode,
+    padding: [u8; 4],
+    // the tiles show their number and no image
+    numbered: bool,
+    // the number of each tile over its image, on a dark backdrop
+    // as opaque as the contrast
+    rank_overlay: bool,
+    rank_font_size: f32,
+    rank_contrast: f32,
+    // the tiles moved slide into place, unless the motion is reduced,
+    // the inputs meanwhile wait their turn
+    slide: Option<Slide>,
+    pending_inputs: VecDeque<PlayerInput>,
+    reduced_motion: bool,
+    // seconds
+    slide_duration: f32,
+    easing: Easing,
+    // the background image as decoded, kept to slice it anew when the size changes
+    image_buffer: RgbaImage,
+    // where the image came from, and its pixels hash, for the save files
+    // and the image opened
+    image_path: Option<String>,
+    image_hash: String,
     image_void_cell:ColorImage,
     image_winner:ColorImage,
-    void_cell: TaquinCoord,
+    // none until the images are uploaded, again after each slicing
+    textures: Option<Textures>,
+    // the fitted image is scaled down to the largest texture the GPU takes,
+    // egui assumes 2048 pixels until the backend tells
+    max_texture_side: usize,
     side_panel_show: bool,
     scrambled: bool,
     trace: bool,
-    // this how you opt-out of serialization of a member
-    //#[cfg_attr(feature = "persistence", serde(skip))]
+    // the arrow keys move the void cell instead of the cell next to it
+    move_hole: bool,
+    // the cell to move next, as the solver says
+    hint: Option<TaquinCoord>,
+    hints_used: usize,
+    solving: Option<PendingSolve>,
+    // the solution played back by the auto-solve
+    playback: Option<Playback>,
+    // auto-solve moves per second
+    playback_speed: f32,
+    // the seed of the last scramble, and its level for a random walk,
+    // no seed for a position pasted
+    seed: Option<u64>,
+    scramble_level: Option<Difficulty>,
+    // the seed as typed in the settings
+    seed_text: String,
+    paste_show: bool,
+    paste_text: String,
+    paste_error: Option<String>,
+    // a list of moves to apply, as typed in the settings
+    moves_text: String,
+    // the save or open window, with the path typed in
+    file_action: Option<FileAction>,
+    // the path typed in for an image, kept apart from the save file
+    image_file_path: String,
+    file_path: String,
+    file_error: Option<String>,
 }
 
 #[warn(unused)]
@@ -55,19 +227,54 @@ impl Default for TemplateApp {
             label: "Hello World!".to_owned(),
             scrambled: false,
             trace: false,
-            cells_map: HashMap::new(),
-            sides: TaquinSide::default(),
+            board: Board::default(),
+            history: History::default(),
+            score: Score::default(),
+            summary_show: false,
             image: egui::ColorImage::example(),
+            slice_mode: SliceMode::Crop,
+            padding: [0, 0, 0, 255],
+            numbered: false,
+            rank_overlay: false,
+            rank_font_size: 20.0,
+            rank_contrast: 0.5,
+            slide: None,
+            pending_inputs: VecDeque::new(),
+            reduced_motion: false,
+            slide_duration: 0.15,
+            easing: Easing::EaseOut,
+            image_buffer: RgbaImage::default(),
+            image_path: None,
+            image_hash: String::new(),
             image_void_cell: egui::ColorImage::example(),
             image_winner: egui::ColorImage::example(),
-            void_cell: TaquinCoord::default(),
+            textures: None,
+            max_texture_side: 2048,
             side_panel_show: false,
+            move_hole: false,
+            hint: None,
+            hints_used: 0,
+            solving: None,
+            playback: None,
+            playback_speed: 2.0,
+            seed: None,
+            scramble_level: None,
+            seed_text: String::new(),
+            paste_show: false,
+            paste_text: String::new(),
+            paste_error: None,
+            moves_text: String::new(),
+            file_action: None,
+            file_path: SAVE_FILE_PATH.to_owned(),
+            image_file_path: String::new(),
+            file_error: None,
         }
     }
 }
 
 impl TemplateApp{
     fn add_central_panel(&mut self, ctx: &egui::Context, _frame: &epi::Frame,){
+        self.load_textures(ctx);
         egui::CentralPanel::default().show(ctx, |ui| {
             // The central panel the region left after adding TopPanel's and SidePanel's
             //
@@ -85,63 +292,342 @@ impl TemplateApp{
                 ));
                 ui.monospace(self.label.as_str());               
             }
-            
+            egui::warn_if_debug_build(ui);
+
+            // T I L E _ S I Z E
+            // from the space left, each frame: the window may be resized
+            // or moved to a screen of another pixel density
+            let mut padding = if self.numbered { Vec2::ZERO } else { Vec2::splat(2.0 * ui.spacing().button_padding.x) };
+            let mut tile_size = self.tile_size(ui.available_size(), padding, ctx.pixels_per_point());
+            if tile_size.min_elem() < FRAMED_TILE_MIN_SIDE{
+                padding = Vec2::ZERO;
+                tile_size = self.tile_size(ui.available_size(), padding, ctx.pixels_per_point());
+            }
+            let framed = padding != Vec2::ZERO;
+            // the tiles sliding are painted over the grid, once it is done
+            let mut sliding = Vec::new();
+            // where each tile is, by index on the board, and the tile under the mouse
+            let mut rects = vec![egui::Rect::NOTHING; self.board.sides().num_cells()];
+            let mut hovered = None;
+
             // G R I D
             egui::Grid::new("taquin_1")
             .spacing(Vec2::new(0.0,0.0))
             .show(ui, |ui| {
 
-                for line in 1..=self.sides.num_line{
-                    for col in 1..=self.sides.num_col{
-
-                        let texture: &egui::TextureHandle;
-                        
-                        let cell = self.cells_map.get(&format!("{col}_{line}")).unwrap();
-                        
-                        let mut texture_opt  = None;
-                        texture = texture_opt.get_or_insert_with(|| {
-                            if ! cell.is_void{
-                                ui.ctx()
-                                    .load_texture(format!("img_col{col}_line{line}"), cell.image.clone())
+                for line in 1..=self.board.sides().num_line{
+                    for col in 1..=self.board.sides().num_col{
+
+                        let rank = self.board.rank(&TaquinCoord{col, line}).unwrap();
+                        let cell = Cell{rank};
+                        let is_void = rank == self.board.void_rank();
+
+                        let offset = self.slide.as_ref().map(|slide| slide.offset(rank)).unwrap_or((0.0, 0.0));
+
+                        let mut response = if offset != (0.0, 0.0){
+                            // the place is kept, the tile comes to it
+                            let (rect, response) = ui.allocate_exact_size(tile_size + padding, egui::Sense::click());
+                            sliding.push((rect, rank, offset));
+                            response
+                        }else if self.numbered{
+                            // a plain tile, the void cell is left empty
+                            let (rect, response) = ui.allocate_exact_size(tile_size, egui::Sense::click());
+                            if ! is_void{
+                                let visuals = ui.style().interact(&response);
+                                ui.painter().rect(rect.shrink(1.0), visuals.rounding, visuals.bg_fill, visuals.bg_stroke);
+                                ui.painter_at(rect).text(
+                                    rect.center(),
+                                    egui::Align2::CENTER_CENTER,
+                                    cell.rank,
+                                    egui::FontId::proportional(self.rank_font_size),
+                                    visuals.text_color(),
+                                );
+                            }
+                            response
+                        }else{
+                            // uploaded at the beginning of the panel
+                            let textures = self.textures.as_ref().expect("the textures are loaded");
+                            let image_button = if ! is_void{
+                                // a part of the image, no copy of its pixels
+                                egui::ImageButton::new(&textures.image, tile_size)
+                                    .uv(cell.uv(self.board.sides()))
                             }else if self.has_won() {
                                 info!("We have a winner.");
-                                ui.ctx()
-                                    .load_texture(format!("img_col{col}_line{line}"), self.image_winner.clone())
+                                egui::ImageButton::new(&textures.winner, tile_size)
                             }else{
-                                ui.ctx()
-                                    .load_texture(format!("img_col{col}_line{line}"), self.image_void_cell.clone())
-                            }
-                        });
+                                egui::ImageButton::new(&textures.void_cell, tile_size)
+                            }.frame(framed);
 
-                        let img_size = 54.0 * texture.size_vec2() / texture.size_vec2().y;
-
-                        if ui.add(egui::ImageButton::new(texture, img_size)).clicked(){
+                            let response = ui.add(image_button);
+                            if self.rank_overlay && ! is_void{
+                                self.paint_rank(ui, response.rect, cell.rank);
+                            }
+                            response
+                        };
+                        if ! is_void && self.board.direction_to(&TaquinCoord{col, line}).1 == Direction::Dontapply{
+                            // out of the line and the column of the void cell, a click does nothing
+                            ui.painter().rect_filled(response.rect, 0.0, egui::Color32::from_black_alpha(90));
+                            response = response.on_hover_cursor(egui::CursorIcon::NotAllowed);
+                        }
+                        if let Some(index) = self.board.sides().index(&TaquinCoord{col, line}){
+                            rects[index] = response.rect;
+                        }
+                        if response.hovered(){
+                            hovered = Some(TaquinCoord{col, line});
+                        }
+                        if self.trace{
+                            response = response.on_hover_text(format!("rank {}", cell.rank));
+                        }
+                        if self.hint == Some(TaquinCoord{col, line}){
+                            ui.painter().rect_stroke(response.rect, 0.0, egui::Stroke::new(3.0, egui::Color32::YELLOW));
+                        }
+                        if response.clicked(){
                            // the click must fall close next to the void cell and cannot be on the void cell
-                            self.on_click_button(col, line);
+                            self.queue_input(PlayerInput::Click(TaquinCoord{col, line}));
                         }            
                     } 
                 ui.end_row();
                 }                
             });
-            egui::warn_if_debug_build(ui);
+
+            let pitch = tile_size + padding;
+            for (rect, rank, (dx, dy)) in sliding{
+                self.paint_sliding_tile(ui, rect.translate(Vec2::new(dx * pitch.x, dy * pitch.y)), padding, rank);
+            }
+
+            if let Some(coord) = hovered{
+                self.paint_click_preview(ui, &coord, &rects);
+            }
         });
     }
 
+    //
+    // The tiles a click on `coord` would shift are outlined,
+    // an arrow on each one shows the way it would go.
+    //
+    fn paint_click_preview(&self, ui: &egui::Ui, coord: &TaquinCoord, rects: &[egui::Rect]){
+        let (delta, direction) = self.board.direction_to(coord);
+        // the tiles go the other way than the void cell
+        let arrow = match direction{
+            Direction::Up => Vec2::new(0.0, 1.0),
+            Direction::Down => Vec2::new(0.0, -1.0),
+            Direction::Left => Vec2::new(1.0, 0.0),
+            Direction::Right => Vec2::new(-1.0, 0.0),
+            Direction::Dontapply => return,
+        };
+        let color = ui.visuals().selection.bg_fill;
+        for cell in self.moved_cells(&Move{direction, delta}){
+            let rect = match self.board.sides().index(&cell){
+                Some(index) => rects[index],
+                None => continue,
+            };
+            let vec = arrow * 0.4 * rect.width().min(rect.height());
+            ui.painter().rect_filled(rect, 0.0, egui::Color32::from_white_alpha(40));
+            ui.painter().rect_stroke(rect.shrink(1.0), 0.0, egui::Stroke::new(2.0, color));
+            ui.painter().arrow(rect.center() - vec / 2.0, vec, egui::Stroke::new(3.0, color));
+        }
+    }
+
+    //
+    // A tile sliding, painted at `rect` over the grid: it looks like
+    // the button it is once in place.
+    //
+    fn paint_sliding_tile(&self, ui: &mut egui::Ui, rect: egui::Rect, padding: Vec2, rank: usize){
+        let visuals = ui.visuals().widgets.inactive;
+        if self.numbered{
+            ui.painter().rect(rect.shrink(1.0), visuals.rounding, visuals.bg_fill, visuals.bg_stroke);
+            ui.painter_at(rect).text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                rank,
+                egui::FontId::proportional(self.rank_font_size),
+                visuals.text_color(),
+            );
+        }else if let Some(textures) = &self.textures{
+            // a small tile has no frame, see `FRAMED_TILE_MIN_SIDE`
+            let framed = padding != Vec2::ZERO;
+            if framed{
+                ui.painter().rect_filled(rect, visuals.rounding, visuals.bg_fill);
+            }
+            egui::Image::new(&textures.image, rect.size() - padding)
+                .uv(Cell{rank}.uv(self.board.sides()))
+                .paint_at(ui, rect.shrink2(padding / 2.0));
+            if framed{
+                ui.painter().rect_stroke(rect, visuals.rounding, visuals.bg_stroke);
+            }
+            if self.rank_overlay{
+                self.paint_rank(ui, rect, rank);
+            }
+        }
+    }
+
+    //
+    // The player is on the game: the page is shown and has the focus,
+    // the clock is paused otherwise. Always on a native window, eframe 0.17
+    // doesn't tell the app about its focus.
+    //
+    #[cfg(target_arch = "wasm32")]
+    fn has_focus() -> bool{
+        web_sys::window()
+            .and_then(|window| window.document())
+            .map(|document| ! document.hidden() && document.has_focus().unwrap_or(true))
+            .unwrap_or(true)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn has_focus() -> bool{
+        true
+    }
+
+    //
+    // The size of the image of a tile, the grid filling the `available`
+    // space with the cells keeping the shape they have in the image.
+    // `padding` is what a button adds around its image, none once the
+    // tiles are too small for a frame. The size is rounded
+    // down to whole pixels so that the tiles line up on a HiDPI screen too.
+    //
+    fn tile_size(&self, available: Vec2, padding: Vec2, pixels_per_point: f32) -> Vec2{
+        let sides = self.board.sides();
+        let aspect = if self.numbered{
+            1.0
+        }else{
+            (self.image.size[0] as f32 / sides.num_col as f32) / (self.image.size[1] as f32 / sides.num_line as f32)
+        };
+        let width = available.x / sides.num_col as f32 - padding.x;
+        let height = available.y / sides.num_line as f32 - padding.y;
+        let height = height.min(width / aspect);
+
+        let size = (Vec2::new(height * aspect, height) * pixels_per_point).floor() / pixels_per_point;
+        size.max(Vec2::splat(1.0 / pixels_per_point))
+    }
+
+    //
+    // The rank of a cell over its image, the backdrop
+    // keeps it readable on a light image.
+    //
+    fn paint_rank(&self, ui: &egui::Ui, rect: egui::Rect, rank: usize){
+        let painter = ui.painter_at(rect);
+        let galley = painter.layout_no_wrap(
+            rank.to_string(), 
+            egui::FontId::proportional(self.rank_font_size), 
+            egui::Color32::WHITE,
+        );
+        let text_rect = egui::Align2::CENTER_CENTER.anchor_rect(egui::Rect::from_min_size(rect.center(), galley.size()));
+        painter.rect_filled(
+            text_rect.expand(2.0), 
+            3.0, 
+            egui::Color32::from_black_alpha((self.rank_contrast * 255.0) as u8),
+        );
+        painter.galley(text_rect.min, galley);
+    }
+
     fn add_side_panel(&mut self, ctx: &egui::Context, _frame: &epi::Frame,){
         egui::SidePanel::right("right_panel").show(ctx, |ui| {
             ui.heading("Settings...");
 //            ui.add(egui::Label::new("Settings"));
            
             ui.checkbox(&mut self.trace, "Trace");
+            ui.checkbox(&mut self.move_hole, "Arrow keys move the hole");
+
+            // S I Z E
+            let mut sides = self.board.sides().clone();
+            ui.add(egui::Slider::new(&mut sides.num_col, MIN_SIDE ..= MAX_SIDE).text("columns"));
+            ui.add(egui::Slider::new(&mut sides.num_line, MIN_SIDE ..= MAX_SIDE).text("lines"));
+            if sides != *self.board.sides(){
+                self.resize(sides);
+            }
+
+            // M O V E S
+            ui.separator();
+            ui.label("Moves (U, D, L, R, R3...)");
+            ui.text_edit_singleline(&mut self.moves_text);
+            ui.horizontal(|ui| {
+                if ui.button("Apply moves").clicked(){
+                    let text = self.moves_text.clone();
+                    self.apply_moves(&text);
+                }
+                if ui.button("Copy moves").clicked(){
+                    ui.output().copied_text = format_moves(self.history.moves());
+                }
+            });
+
+            // S E E D
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Seed");
+                ui.text_edit_singleline(&mut self.seed_text);
+            });
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("scramble_level")
+                .selected_text(Self::level_name(self.scramble_level))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.scramble_level, None, Self::level_name(None));
+                    for difficulty in Difficulty::ALL{
+                        ui.selectable_value(&mut self.scramble_level, Some(difficulty), Self::level_name(Some(difficulty)));
+                    }
+                });
+                if ui.button("Scramble with seed").clicked(){
+                    match self.seed_text.trim().parse::<u64>(){
+                        Ok(seed) => self.scramble_seeded(self.scramble_level, seed),
+                        Err(_) => self.label = format!("Invalid seed \"{}\", a number is expected", self.seed_text.trim()),
+                    }
+                }
+            });
+            ui.separator();
 
-            let mut texture_opt  = None;
-            let texture: &egui::TextureHandle = texture_opt.get_or_insert_with(|| {
-                // Load the texture only once.
-                ui.ctx().load_texture("main-image", self.image.clone())
+            // S L I C I N G
+            let (slice_mode, padding) = (self.slice_mode, self.padding);
+            ui.horizontal(|ui| {
+                ui.label("Image");
+                egui::ComboBox::from_id_source("slice_mode")
+                .selected_text(format!("{:?}", self.slice_mode))
+                .show_ui(ui, |ui| {
+                    for mode in SliceMode::ALL{
+                        ui.selectable_value(&mut self.slice_mode, mode, format!("{:?}", mode));
+                    }
+                });
+                if self.slice_mode == SliceMode::Letterbox{
+                    ui.color_edit_button_srgba_unmultiplied(&mut self.padding);
+                }
             });
+            if (slice_mode, padding) != (self.slice_mode, self.padding){
+                // the cells get their new images, the game goes on
+                self.slice_image();
+            }
+
+            // N U M B E R S
+            ui.checkbox(&mut self.numbered, "Numbered tiles, no image");
+            ui.add_enabled(! self.numbered, egui::Checkbox::new(&mut self.rank_overlay, "Numbers over the image"));
+            ui.add(egui::Slider::new(&mut self.rank_font_size, 8.0 ..= 40.0).text("number size"));
+            ui.add_enabled(! self.numbered && self.rank_overlay, 
+                egui::Slider::new(&mut self.rank_contrast, 0.0 ..= 1.0).text("number contrast"));
 
-            // Show the image:
-            ui.image(texture, texture.size_vec2());            
+            // M O T I O N
+            ui.separator();
+            ui.checkbox(&mut self.reduced_motion, "Reduced motion, no slide");
+            ui.add_enabled(! self.reduced_motion, 
+                egui::Slider::new(&mut self.slide_duration, 0.05 ..= 1.0).text("slide duration (s)"));
+            ui.add_enabled_ui(! self.reduced_motion, |ui| {
+                egui::ComboBox::from_label("easing")
+                .selected_text(format!("{:?}", self.easing))
+                .show_ui(ui, |ui| {
+                    for easing in Easing::ALL{
+                        ui.selectable_value(&mut self.easing, easing, format!("{:?}", easing));
+                    }
+                });
+            });
+            if self.reduced_motion{
+                self.slide = None;
+            }
+            ui.separator();
+
+            // the image may have been sliced anew above
+            self.load_textures(ui.ctx());
+            if let Some(textures) = &self.textures{
+                // Show the image, as wide as the panel:
+                let size = textures.image.size_vec2();
+                ui.image(&textures.image, size * (ui.available_width() / size.x));
+            }
         });
     }
 
@@ -151,7 +637,43 @@ impl TemplateApp{
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
                     if ui.button("Scramble").clicked() {
-                        self.cells_map = self.scramble();
+                        self.scramble(None);
+                    }
+                    ui.menu_button("Scramble by level", |ui| {
+                        for difficulty in Difficulty::ALL{
+                            if ui.button(format!("{:?}", difficulty)).clicked() {
+                                self.scramble(Some(difficulty));
+                            }
+                        }
+                    });
+                    if ui.button("Open image…").clicked() {
+                        self.file_action = Some(FileAction::OpenImage);
+                        self.file_error = None;
+                    }
+                    // no file system in a browser
+                    if cfg!(not(target_arch = "wasm32")) {
+                        if ui.button("Save game…").clicked() {
+                            self.file_action = Some(FileAction::Save);
+                            self.file_error = None;
+                        }
+                        if ui.button("Open game…").clicked() {
+                            self.file_action = Some(FileAction::Open);
+                            self.file_error = None;
+                        }
+                    }
+                    if ui.button("Copy position").clicked() {
+                        ui.output().copied_text = self.board.to_string();
+                        self.label = format!("Position copied: {}", self.board);
+                    }
+                    if ui.button("Paste position").clicked() {
+                        self.paste_show = true;
+                        self.paste_error = None;
+                    }
+                    if ui.add_enabled(self.history.can_undo(), egui::Button::new("Undo     Ctrl+Z")).clicked() {
+                        self.queue_input(PlayerInput::Undo);
+                    }
+                    if ui.add_enabled(self.history.can_redo(), egui::Button::new("Redo     Ctrl+Y")).clicked() {
+                        self.queue_input(PlayerInput::Redo);
                     }
                     if ui.button("Settings...").clicked() {
                         self.side_panel_show = !self.side_panel_show;
@@ -160,132 +682,844 @@ impl TemplateApp{
                         frame.quit();
                     }
                 });
+                if ui.add_enabled(self.solving.is_none(), egui::Button::new("Hint")).clicked() {
+                    self.start_solving(SolveFor::Hint, HINT_MAX_NODES);
+                }
+                if ui.add_enabled(self.solving.is_none(), egui::Button::new("Auto-solve")).clicked() {
+                    self.start_solving(SolveFor::AutoSolve, AUTO_SOLVE_MAX_NODES);
+                }
+                ui.label(format!("Hints used: {}", self.hints_used));
+                ui.separator();
+                ui.label(format!("Moves: {}", self.score.moves()));
+                ui.label(format!("Time: {}", self.score.time()));
+                if let (true, Some(seed)) = (self.scrambled, self.seed){
+                    ui.separator();
+                    ui.label(format!("Seed: {} ({})", seed, Self::level_name(self.scramble_level)));
+                }
             });
         });
     }
 
     fn on_click_button(&mut self, col:usize, line:usize){
 
-        let (delta , direction) = match (col, line, self.void_cell.col, self.void_cell.line){
-            // same col click above void_cell
-            (c,l,vcc, vcl) if c == vcc && l < vcl => (vcl - l, Direction::Up),
-            // same col click below void_cell
-            (c,l,vcc, vcl) if c == vcc && l > vcl => (l - vcl, Direction::Down),
-            // same line click before void_cell
-            (c,l,vcc, vcl) if l == vcl && c < vcc => (vcc - c, Direction::Left),
-            // same line click after void_cell
-            (c,l,vcc, vcl) if l == vcl && c > vcc => (c - vcc, Direction::Right),
-            (_,_,_,_) => (0, Direction::Dontapply),
+        // from the void_cell shifts to the cell clicked
+        let (delta, direction) = self.board.direction_to(&TaquinCoord{col, line});
+        let mv = Move{direction, delta};
+
+        if self.play(&mv){
+            self.history.push(mv);
+            // do a little trace displayed in the app
+            self.label = format!("On click current col {col}, line {line}, void_cell col {} line {}, {}",
+                self.board.void_cell().col,
+                self.board.void_cell().line, 
+                self.solvability(),
+            );
+        }else{
+            self.label = "".to_owned();
+        }
+    }
+
+
+    //
+    // Every move goes through here, whether it comes from a click,
+    // a key or the history. Returns false when nothing moved.
+    //
+    fn play(&mut self, mv: &Move) -> bool{
+        let moved = if self.reduced_motion { Vec::new() } else { self.moved_ranks(mv) };
+        if ! self.board.apply(mv){
+            return false;
+        }
+        self.slide = if moved.is_empty(){
+            None
+        }else{
+            Some(Slide::new(moved, mv.direction, self.slide_duration, self.easing))
         };
+        self.hint = None;
+        self.score.record(mv);
+        if self.has_won() && self.score.is_running(){
+            self.score.stop();
+            self.summary_show = true;
+        }
+        true
+    }
 
-        debug!("delta {}, direction {:?}",delta, direction);
+    //
+    // The cells `mv` shifts, before it is played:
+    // from the one next to the void cell on.
+    //
+    fn moved_cells(&self, mv: &Move) -> Vec<TaquinCoord>{
+        let void_cell = *self.board.void_cell();
+        (1 ..= mv.delta).map_while(|step| {
+            let coord = match mv.direction{
+                Direction::Up => TaquinCoord{line: void_cell.line.checked_sub(step)?, ..void_cell},
+                Direction::Down => TaquinCoord{line: void_cell.line + step, ..void_cell},
+                Direction::Left => TaquinCoord{col: void_cell.col.checked_sub(step)?, ..void_cell},
+                Direction::Right => TaquinCoord{col: void_cell.col + step, ..void_cell},
+                Direction::Dontapply => return None,
+            };
+            self.board.sides().index(&coord).map(|_| coord)
+        }).collect()
+    }
 
-        if direction != Direction::Dontapply{
-            for _ in 0 .. delta{
-                // from the void_cell shifts to the cell clicked
-                
-                let void_cell = self.cells_map.get(&format!("{}_{}",self.void_cell.col, self.void_cell.line)).unwrap();
-                let void_cell_image = void_cell.image.clone();
-                let void_cell_rank = void_cell.rank;   
+    //
+    // The ranks of the cells `mv` shifts, before it is played.
+    //
+    fn moved_ranks(&self, mv: &Move) -> Vec<usize>{
+        let cells = self.moved_cells(mv);
+        if cells.len() != mv.delta{
+            // off the board, the move won't be played
+            return Vec::new();
+        }
+        cells.iter().filter_map(|coord| self.board.rank(coord)).collect()
+    }
 
-                // update void_cell of the app objet
-                // go get the current void_cell to put it to void
-                let c = self.void_cell.col;
-                let l = self.void_cell.line;
+    //
+    // Inputs are run in turn, each one once the tiles
+    // moved by the previous one are in place.
+    //
+    fn queue_input(&mut self, input: PlayerInput){
+        self.pending_inputs.push_back(input);
+        self.run_pending_inputs();
+    }
 
+    fn run_pending_inputs(&mut self){
+        while self.slide.is_none(){
+            match self.pending_inputs.pop_front(){
+                Some(PlayerInput::Click(coord)) => {
+                    self.playback = None;
+                    self.on_click_button(coord.col, coord.line);
+                },
+                Some(PlayerInput::Key(direction)) => self.on_key_direction(direction),
+                Some(PlayerInput::Undo) => self.undo(),
+                Some(PlayerInput::Redo) => self.redo(),
+                None => break,
+            }
+        }
+    }
 
-                debug!("before col {}, line {}", c, l);
+    //
+    // The slide goes on, the inputs waiting are run once it is over.
+    //
+    fn tick_slide(&mut self, ctx: &egui::Context){
+        if let Some(slide) = &mut self.slide{
+            slide.tick(ctx.input().unstable_dt);
+            if slide.is_finished(){
+                self.slide = None;
+            }else{
+                ctx.request_repaint();
+            }
+        }
+        self.run_pending_inputs();
+    }
 
-                let (c, l) = match (direction, c, l){
-                    (Direction::Up,    c, l)  => (c, l - 1,),
-                    (Direction::Down,  c, l)  => (c, l + 1,),
-                    (Direction::Left,  c, l)  => (c - 1 , l,),
-                    (Direction::Right, c, l)  => (c + 1, l,),
-                    (_,_,_) => panic!("Invalid direction"),
+    //
+    // The search runs aside, `poll_solving` takes its result.
+    // One search at a time.
+    //
+    fn start_solving(&mut self, purpose: SolveFor, max_nodes: u64){
+        if self.solving.is_some(){
+            return;
+        }
+        self.solving = Some(PendingSolve{
+            purpose,
+            ranks: self.board.ranks().to_vec(),
+            solving: Solving::start(&self.board, max_nodes),
+        });
+        self.label = "Searching...".to_owned();
+    }
+
+    fn poll_solving(&mut self, ctx: &egui::Context){
+        let pending = match &mut self.solving{
+            Some(pending) => pending,
+            None => return,
+        };
+        if pending.ranks != self.board.ranks(){
+            self.solving = None;
+            self.label = "Search dropped, the board moved".to_owned();
+            return;
+        }
+        match pending.solving.poll(){
+            Some(solution) => {
+                let purpose = pending.purpose;
+                self.solving = None;
+                match purpose{
+                    SolveFor::Hint => self.show_hint(solution),
+                    SolveFor::AutoSolve => self.auto_solve(solution),
+                }
+            },
+            // nothing else may be asking for a frame
+            None => ctx.request_repaint(),
+        }
+    }
+
+    //
+    // The cell to move next is outlined.
+    //
+    fn show_hint(&mut self, solution: Option<Solution>){
+        match solution{
+            Some(solution) => match solution.directions.first(){
+                Some(&direction) => {
+                    self.hint = self.board.neighbour(direction);
+                    self.hints_used += 1;
+                    self.label = if solution.optimal{
+                        format!("Hint: {} moves left", solution.directions.len())
+                    }else{
+                        format!("Hint: solvable in {} moves, maybe less", solution.directions.len())
+                    };
+                },
+                None => self.label = "Already solved".to_owned(),
+            },
+            None => self.label = "No hint found".to_owned(),
+        }
+    }
+
+    //
+    // The solution from the current position is played back move after move.
+    //
+    fn auto_solve(&mut self, solution: Option<Solution>){
+        match solution{
+            Some(Solution{directions, optimal}) => {
+                let length = if optimal{
+                    format!("{} moves", directions.len())
+                }else{
+                    format!("{} moves, not the shortest", directions.len())
+                };
+                self.label = if directions.len() <= AUTO_SOLVE_MAX_LISTED{
+                    format!("Auto-solve in {}: {}", length, format_moves(&moves_from_directions(&directions)))
+                }else{
+                    format!("Auto-solve in {}", length)
                 };
+                self.playback = Some(Playback::new(directions, self.playback_speed));
+            },
+            None => self.label = "No solution found".to_owned(),
+        }
+    }
+
+    //
+    // The playback moves the board the way a click does,
+    // on the cell next to the void cell.
+    //
+    fn on_playback_direction(&mut self, direction: Direction){
+        if let Some(coord) = self.board.neighbour(direction){
+            self.on_click_button(coord.col, coord.line);
+        }
+    }
 
-                debug!("after col {}, line {}", c, l);
-
-                // Change cell clicked
-                let mut cell = self.cells_map.get_mut(&format!("{}_{}", c, l)).unwrap();
-                cell.is_void = true;
-                let content_image = cell.image.clone();
-                let content_rank = cell.rank;
-
-                cell.image = void_cell_image.clone();
-                cell.rank = void_cell_rank;
-
-                // Change former void_cell
-                let mut cell = self.cells_map.get_mut(&format!("{}_{}", self.void_cell.col, self.void_cell.line)).unwrap();
-                cell.is_void = false;
-                cell.image = content_image;
-                cell.rank = content_rank;
-
-                // actualize the void_cell info in the app object
-                self.void_cell.col  = c;
-                self.void_cell.line = l;
-            
-                // do a little trace displayed in the app
-                self.label = format!("On click current col {col}, line {line}, void_cell col {} line {}",
-                    self.void_cell.col,
-                    self.void_cell.line, 
-                );
+    //
+    // Playback controls, and the moves due since the last frame.
+    //
+    fn add_playback_window(&mut self, ctx: &egui::Context){
+        let mut playback = match self.playback.take(){
+            Some(playback) => playback,
+            None => return,
+        };
+        let mut open = true;
+        let mut step = None;
+        let mut back = false;
+        // a step waits for the tiles to be in place, as the timed moves do
+        let idle = self.slide.is_none();
 
-           
+        egui::Window::new("Auto-solve")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Move {} / {}", playback.position(), playback.len()));
+            if ui.add(egui::Slider::new(&mut playback.speed, 0.5 ..= 10.0).text("moves per second")).changed(){
+                self.playback_speed = playback.speed;
             }
-        }else{
-            self.label = "".to_owned();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(idle && playback.position() > 0, egui::Button::new("Step back")).clicked(){
+                    back = playback.step_back().is_some();
+                }
+                let pause = if playback.paused { "Play" } else { "Pause" };
+                if ui.button(pause).clicked(){
+                    playback.paused = ! playback.paused;
+                }
+                if ui.add_enabled(idle && ! playback.is_finished(), egui::Button::new("Step forward")).clicked(){
+                    step = playback.step_forward();
+                }
+            });
+        });
+
+        // the next move waits for the tiles to be in place
+        if step.is_none() && ! back && idle && playback.tick(ctx.input().unstable_dt as f64){
+            step = playback.step_forward();
+        }
+        if back{
+            // the last move played is taken back from the history, not
+            // played anew; the playback is put back below
+            self.undo();
+        }else if let Some(direction) = step{
+            self.on_playback_direction(direction);
+        }
+        if ! playback.paused && ! playback.is_finished(){
+            ctx.request_repaint();
+        }
+        if open{
+            self.playback = Some(playback);
         }
     }
 
+    //
+    // The summary of the game, once won.
+    //
+    fn add_summary_window(&mut self, ctx: &egui::Context){
+        egui::Window::new("We have a winner!")
+        .open(&mut self.summary_show)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(format!("Moves: {}", self.score.moves()));
+            ui.label(format!("Cells moved: {}", self.score.tiles()));
+            ui.label(format!("Time: {}", self.score.time()));
+            ui.label(format!("Hints used: {}", self.hints_used));
+        });
+    }
 
     //
-    // Scramble the image, returns a new hashmap.
-    //
-    fn scramble(&mut self,)-> HashMap<String, Cell,>{
-
-        let mut rng = thread_rng();
-        let mut hash:HashMap<String, Cell,> = HashMap::new();
-   
-        let size = self.sides.num_col* self.sides.num_line;
-
-        let mut vect_rank:Vec<usize>= Vec::new();
-
-        // a duplicate of the hashmap of the grid is build
-        // but the rank of the cells is randomized.
-        // The new hashmap is returned at the end.
-        // The void cell is set anew.
-        for c in 1 .. self.sides.num_col + 1 {
-            for l in 1 .. self.sides.num_line + 1{
-                // 
-                loop{
-                    let random = rng.gen_range(1..= size);
-                    
-                    if ! vect_rank.contains(&random){
-                        vect_rank.push(random);
-                        for (_,v) in self.cells_map.iter(){
-                            if v.rank == random{
-                                let cell = Cell{
-                                        image: v.image.clone(),
-                                        rank: random,
-                                        is_void: v.is_void,                                                
-                                };
-                                // don't forget to update the void_cell
-                                if cell.is_void{
-                                    self.void_cell.col = c;
-                                    self.void_cell.line = l;
-                                };
-                                hash.insert(format!("{}_{}", c, l), cell,);
-                                break;              
-                            }
-                        }
-                        break;
+    // Take back the last move.
+    //
+    fn undo(&mut self){
+        self.playback = None;
+        if let Some(mv) = self.history.undo(){
+            self.play(&mv);
+            self.label = format!("Undo {:?} {}", mv.direction, mv.delta);
+        }
+    }
+
+    //
+    // Play again the last move taken back.
+    //
+    fn redo(&mut self){
+        self.playback = None;
+        if let Some(mv) = self.history.redo(){
+            self.play(&mv);
+            self.label = format!("Redo {:?} {}", mv.direction, mv.delta);
+        }
+    }
+
+    //
+    // An arrow key slides the cell next to the void cell into it,
+    // `direction` is the way the arrow points.
+    //
+    fn on_key_direction(&mut self, direction: Direction){
+        self.playback = None;
+        let mv = Move{
+            // the cell moving into the void cell goes the other way
+            direction: if self.move_hole { direction } else { direction.opposite() },
+            delta: 1,
+        };
+        if self.play(&mv){
+            self.history.push(mv);
+            self.label = format!("On key {:?}, void_cell col {} line {}, {}",
+                direction,
+                self.board.void_cell().col,
+                self.board.void_cell().line, 
+                self.solvability(),
+            );
+        }
+    }
+
+    //
+    // Ctrl+Z and Ctrl+Y, Cmd on a mac. The arrow keys and WASD.
+    // None of them when a text field has the focus, they are its own keys.
+    //
+    fn handle_shortcuts(&mut self, ctx: &egui::Context){
+        if ctx.wants_keyboard_input(){
+            return;
+        }
+        let (undo, redo) = {
+            let mut input = ctx.input_mut();
+            (input.consume_key(egui::Modifiers::COMMAND, egui::Key::Z),
+             input.consume_key(egui::Modifiers::COMMAND, egui::Key::Y))
+        };
+        if undo{
+            self.queue_input(PlayerInput::Undo);
+        }
+        if redo{
+            self.queue_input(PlayerInput::Redo);
+        }
+
+        let keys = [
+            (egui::Key::ArrowUp, Direction::Up),
+            (egui::Key::W, Direction::Up),
+            (egui::Key::ArrowDown, Direction::Down),
+            (egui::Key::S, Direction::Down),
+            (egui::Key::ArrowLeft, Direction::Left),
+            (egui::Key::A, Direction::Left),
+            (egui::Key::ArrowRight, Direction::Right),
+            (egui::Key::D, Direction::Right),
+        ];
+        for (key, direction) in keys{
+            let pressed = ctx.input_mut().consume_key(egui::Modifiers::NONE, key);
+            if pressed{
+                self.queue_input(PlayerInput::Key(direction));
+            }
+        }
+    }
+
+    //
+    // Change the size of the grid, the image is sliced anew
+    // and the board starts solved.
+    //
+    fn resize(&mut self, sides: TaquinSide){
+        self.board = Board::new(sides);
+        self.playback = None;
+        self.slide = None;
+        self.pending_inputs.clear();
+        self.score = Score::default();
+        self.summary_show = false;
+        self.history.clear();
+        self.hint = None;
+        self.hints_used = 0;
+        self.scrambled = false;
+        self.slice_image();
+        self.label = format!("New grid {} columns, {} lines", 
+            self.board.sides().num_col, 
+            self.board.sides().num_line,
+        );
+    }
+
+    //
+    // Upload the images to the GPU, unless they are already.
+    //
+    fn load_textures(&mut self, ctx: &egui::Context){
+        if self.textures.is_some(){
+            return;
+        }
+        self.textures = Some(Textures{
+            image: ctx.load_texture("main-image", self.image.clone()),
+            void_cell: ctx.load_texture("void_cell", self.image_void_cell.clone()),
+            winner: ctx.load_texture("winner", self.image_winner.clone()),
+        });
+    }
+
+    //
+    // Slice the background image into the cells of the grid: once fitted
+    // to the grid the cells are all the same size, each one shows its part
+    // of the image (see `Cell::uv`), the pixels are not copied.
+    // The texture is uploaded anew on the next frame.
+    //
+    fn slice_image(&mut self){
+        let image_buffer = self.slice_mode.fit(&self.image_buffer, self.board.sides(), self.padding, self.max_texture_side as u32);
+
+        self.image = egui::ColorImage::from_rgba_unmultiplied(
+            [image_buffer.width() as _, image_buffer.height() as _], 
+            image_buffer.as_flat_samples().as_slice(),
+        );
+
+        self.textures = None;
+    }
+
+    //
+    // Scramble the board from a new seed, the cells keep their images.
+    // With a level, the board is scrambled by random moves from the solved board.
+    //
+    fn scramble(&mut self, level: Option<Difficulty>){
+        let seed = thread_rng().gen();
+        self.scramble_seeded(level, seed);
+    }
+
+    //
+    // The same seed and level always give the same board.
+    //
+    fn scramble_seeded(&mut self, level: Option<Difficulty>, seed: u64){
+        match level{
+            None => self.board.scramble(seed),
+            Some(difficulty) => self.board.scramble_walk(difficulty.steps(self.board.sides()), seed),
+        }
+        self.seed = Some(seed);
+        self.seed_text = seed.to_string();
+        self.scramble_level = level;
+        self.new_game();
+        self.label = format!("Scrambled, seed {} ({}), {}", 
+            seed, 
+            Self::level_name(level), 
+            self.solvability(),
+        );
+    }
+
+    //
+    // Play a list of moves given as text, each one can be taken back.
+    // Nothing is played when one of them would leave the board.
+    //
+    fn apply_moves(&mut self, text: &str){
+        let moves = match parse_moves(text, self.board.sides()){
+            Ok(moves) => moves,
+            Err(error) => {
+                self.label = format!("Invalid moves: {}", error);
+                return;
+            },
+        };
+        if let Err(i) = self.board.clone().apply_sequence(&moves){
+            self.label = format!("Move {} ({}) leaves the board", i + 1, moves[i]);
+            return;
+        }
+        self.playback = None;
+        for mv in moves.iter(){
+            self.play(mv);
+            self.history.push(*mv);
+        }
+        // no slide for a whole sequence
+        self.slide = None;
+        self.label = format!("{} moves applied, {}", moves.len(), self.solvability());
+    }
+
+    //
+    // The game in progress, as written in a save file.
+    //
+    fn saved_game(&self) -> SavedGame{
+        let void_cell = self.board.void_cell();
+        SavedGame{
+            version: SAVE_VERSION,
+            num_col: self.board.sides().num_col,
+            num_line: self.board.sides().num_line,
+            cells: self.board.ranks().to_vec(),
+            void_cell: SavedCoord{
+                col: void_cell.col,
+                line: void_cell.line,
+            },
+            image_path: self.image_path.clone(),
+            image_hash: self.image_hash.clone(),
+            moves: self.history.moves().iter().map(|mv| mv.to_string()).collect::<Vec<_>>().join(" "),
+            move_count: self.score.moves(),
+            tile_count: self.score.tiles(),
+            elapsed: self.score.elapsed(),
+            seed: self.seed,
+        }
+    }
+
+    fn save_game(&self, path: &str) -> Result<(), LoadError>{
+        std::fs::write(path, self.saved_game().to_json())?;
+        Ok(())
+    }
+
+    //
+    // The save is checked before anything changes, the grid is resized if needed.
+    //
+    fn open_game(&mut self, path: &str) -> Result<(), LoadError>{
+        let json = std::fs::read_to_string(path)?;
+        let game = SavedGame::from_json(&json, &self.image_hash)?;
+
+        if game.board.sides() != self.board.sides(){
+            self.resize(game.board.sides().clone());
+        }
+        self.board = game.board;
+        self.new_game();
+        self.history = History::with_moves(game.moves);
+        self.score.resume(game.move_count, game.tile_count, game.elapsed);
+        self.seed = game.seed;
+        self.label = format!("Game opened from {}, {}", path, self.solvability());
+        Ok(())
+    }
+
+    //
+    // The path of the save file is typed in.
+    //
+    fn add_file_window(&mut self, ctx: &egui::Context){
+        let action = match self.file_action{
+            Some(action) => action,
+            None => return,
+        };
+        let mut open = true;
+        let mut confirm = false;
+
+        let (title, button) = match action{
+            FileAction::Save => ("Save game", "Save"),
+            FileAction::Open => ("Open game", "Open"),
+            FileAction::OpenImage => ("Open image", "Open"),
+        };
+        egui::Window::new(title)
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if action == FileAction::OpenImage{
+                ui.label("An image file can be dropped on the window too.");
+            }
+            // no file system in a browser, the image can only be dropped
+            if action != FileAction::OpenImage || cfg!(not(target_arch = "wasm32")){
+                ui.horizontal(|ui| {
+                    ui.label("File");
+                    if action == FileAction::OpenImage{
+                        ui.text_edit_singleline(&mut self.image_file_path);
+                    }else{
+                        ui.text_edit_singleline(&mut self.file_path);
+                    }
+                });
+            }
+            if let Some(error) = &self.file_error{
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            if action != FileAction::OpenImage || cfg!(not(target_arch = "wasm32")){
+                confirm = ui.button(button).clicked();
+            }
+        });
+
+        if confirm{
+            let path = if action == FileAction::OpenImage { self.image_file_path.clone() } else { self.file_path.clone() };
+            let result = match action{
+                FileAction::Save => self.save_game(&path).map_err(|error| error.to_string()),
+                FileAction::Open => self.open_game(&path).map_err(|error| error.to_string()),
+                FileAction::OpenImage => self.open_image(&path),
+            };
+            match result{
+                Ok(()) => {
+                    open = false;
+                    if action == FileAction::Save{
+                        self.label = format!("Game saved to {}", path);
+                    }
+                },
+                Err(error) => self.file_error = Some(error),
+            }
+        }
+        if ! open{
+            self.file_action = None;
+        }
+    }
+
+    //
+    // A new image to play on: the grid keeps its size
+    // and the board starts solved.
+    //
+    fn set_image(&mut self, image: DynamicImage, path: Option<String>) -> Result<(), String>{
+        if image.width() < MIN_IMAGE_SIDE || image.height() < MIN_IMAGE_SIDE{
+            return Err(format!("the image is {}x{} pixels, {}x{} at least are needed",
+                image.width(), image.height(), MIN_IMAGE_SIDE, MIN_IMAGE_SIDE));
+        }
+        let image_buffer = image.to_rgba8();
+
+        self.image_hash = image_hash(
+            image_buffer.width() as _, 
+            image_buffer.height() as _, 
+            image_buffer.as_flat_samples().as_slice(),
+        );
+        self.image_path = path;
+        self.image_buffer = image_buffer;
+        self.resize(self.board.sides().clone());
+        Ok(())
+    }
+
+    //
+    // One of the default images: the file at `path` when there is one
+    // which decodes, else the image built in. The path is given back
+    // when the file is used.
+    //
+    fn default_image(path: &str, embedded: &[u8]) -> (DynamicImage, Option<String>){
+        #[cfg(not(target_arch = "wasm32"))]
+        if std::path::Path::new(path).exists(){
+            match image::open(path){
+                Ok(image) => {
+                    info!("Image {} replaces the one built in", path);
+                    return (image, Some(path.to_owned()));
+                },
+                Err(error) => warn!("Image {} ignored: {}", path, error),
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = path;
+
+        let image = image::load_from_memory(embedded).expect("the images built in are valid");
+        (image, None)
+    }
+
+    fn open_image(&mut self, path: &str) -> Result<(), String>{
+        let image = image::open(path).map_err(|error| format!("{}: {}", path, error))?;
+        self.set_image(image, Some(path.to_owned()))?;
+        self.label = format!("Image opened from {}", path);
+        Ok(())
+    }
+
+    //
+    // Image files dropped on the window, the last one decoded wins.
+    // The native window gives a path, the browser gives the bytes.
+    //
+    fn handle_dropped_files(&mut self, ctx: &egui::Context){
+        let dropped_files = std::mem::take(&mut ctx.input_mut().raw.dropped_files);
+        for file in dropped_files{
+            let (name, image) = match (&file.path, &file.bytes){
+                (_, Some(bytes)) => (file.name.clone(), image::load_from_memory(bytes)),
+                (Some(path), None) => (path.display().to_string(), image::open(path)),
+                (None, None) => continue,
+            };
+            let path = file.path.as_ref().map(|path| path.display().to_string());
+            let result = image
+                .map_err(|error| error.to_string())
+                .and_then(|image| self.set_image(image, path));
+            match result{
+                Ok(()) => {
+                    self.file_error = None;
+                    if self.file_action == Some(FileAction::OpenImage){
+                        self.file_action = None;
                     }
-                }    
+                    self.label = format!("Image dropped: {}", name);
+                },
+                Err(error) => {
+                    // the error is shown in the open image window
+                    self.file_action = Some(FileAction::OpenImage);
+                    self.file_error = Some(format!("{}: {}", name, error));
+                },
             }
         }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn persisted_state(&self) -> PersistedState{
+        let sides = self.board.sides();
+        PersistedState{
+            num_col: sides.num_col,
+            num_line: sides.num_line,
+            cells: self.board.ranks().to_vec(),
+            void_col: self.board.void_cell().col,
+            void_line: self.board.void_cell().line,
+            scrambled: self.scrambled,
+            seed: self.seed,
+            scramble_level: self.scramble_level,
+            side_panel_show: self.side_panel_show,
+            trace: self.trace,
+            move_hole: self.move_hole,
+            playback_speed: self.playback_speed,
+            slice_mode: self.slice_mode,
+            padding: self.padding,
+            numbered: self.numbered,
+            rank_overlay: self.rank_overlay,
+            rank_font_size: self.rank_font_size,
+            rank_contrast: self.rank_contrast,
+            reduced_motion: self.reduced_motion,
+            slide_duration: self.slide_duration,
+            easing: self.easing,
+        }
+    }
+
+    //
+    // Back to the last game, once the image is loaded: the grid is
+    // resized and sliced if needed. A state which doesn't hold is dropped.
+    //
+    #[cfg(feature = "persistence")]
+    fn restore(&mut self, state: PersistedState){
+        self.side_panel_show = state.side_panel_show;
+        self.trace = state.trace;
+        self.move_hole = state.move_hole;
+        self.playback_speed = state.playback_speed;
+        self.numbered = state.numbered;
+        self.rank_overlay = state.rank_overlay;
+        self.rank_font_size = state.rank_font_size;
+        self.rank_contrast = state.rank_contrast;
+        self.reduced_motion = state.reduced_motion;
+        self.slide_duration = state.slide_duration;
+        self.easing = state.easing;
+        if (state.slice_mode, state.padding) != (self.slice_mode, self.padding){
+            self.slice_mode = state.slice_mode;
+            self.padding = state.padding;
+            self.slice_image();
+        }
+
+        let sides = TaquinSide{
+            num_col: state.num_col,
+            num_line: state.num_line,
+        };
+        let void_cell = TaquinCoord{
+            col: state.void_col,
+            line: state.void_line,
+        };
+        // a damaged state is dropped, the game starts anew
+        if ! sides.is_valid(){
+            return;
+        }
+        let board = match Board::from_ranks(sides, state.cells){
+            Some(board) if *board.void_cell() == void_cell => board,
+            _ => return,
+        };
+
+        if board.sides() != self.board.sides(){
+            self.resize(board.sides().clone());
+        }
+        self.board = board;
+        if state.scrambled{
+            self.seed = state.seed;
+            self.seed_text = state.seed.map(|seed| seed.to_string()).unwrap_or_default();
+            self.scramble_level = state.scramble_level;
+            self.new_game();
+            self.label = format!("Last game restored, {}", self.solvability());
+        }
+    }
+
+    //
+    // Play from a position given as text, the grid is resized if needed.
+    //
+    fn load_position(&mut self, text: &str) -> Result<(), ParsePositionError>{
+        let board: Board = text.parse()?;
+        if board.sides() != self.board.sides(){
+            self.resize(board.sides().clone());
+        }
+        self.board = board;
+        self.seed = None;
+        self.new_game();
+        self.label = format!("Position pasted, {}", self.solvability());
+        Ok(())
+    }
+
+    //
+    // The position is pasted in a text field, egui can't read the clipboard.
+    //
+    fn add_paste_window(&mut self, ctx: &egui::Context){
+        let mut open = self.paste_show;
+        let mut load = false;
+
+        egui::Window::new("Paste position")
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label("3x2:1,2,3,4,5,0 or one line of the board per line, 0 is the void cell");
+            ui.text_edit_multiline(&mut self.paste_text);
+            if let Some(error) = &self.paste_error{
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            load = ui.button("Load").clicked();
+        });
+
+        if load{
+            let text = self.paste_text.clone();
+            match self.load_position(&text){
+                Ok(()) => {
+                    self.paste_error = None;
+                    open = false;
+                },
+                Err(error) => self.paste_error = Some(error.to_string()),
+            }
+        }
+        self.paste_show = open;
+    }
+
+    fn level_name(level: Option<Difficulty>) -> String{
+        match level{
+            None => "Shuffle".to_owned(),
+            Some(difficulty) => format!("{:?}", difficulty),
+        }
+    }
+
+    //
+    // A scrambled board is a new game.
+    //
+    fn new_game(&mut self){
+        self.playback = None;
+        self.slide = None;
+        self.pending_inputs.clear();
+        self.score.start();
+        self.summary_show = false;
+        self.history.clear();
+        self.hint = None;
+        self.hints_used = 0;
         self.scrambled = true;
-        hash    
+    }
+
+    //
+    // Solvability of the current grid, for the trace label.
+    //
+    fn solvability(&self) -> &'static str{
+        if self.board.is_solvable(){
+            "solvable"
+        }else{
+            "unsolvable"
+        }
     }
 
     //
@@ -293,23 +1527,7 @@ impl TemplateApp{
     // then we win.
     //
     fn has_won(&self) -> bool{
-            if self.scrambled{ 
-                let mut last = 0;
-
-            for l in 1 ..= self.sides.num_line{
-                for c in 1 ..= self.sides.num_col{
-                    let cell = self.cells_map.get(&format!("{c}_{l}")).unwrap();
-                    last += 1;
-                    let rank = cell.rank;  
-                    if rank != last{
-                        return false
-                    }
-                };
-            }
-            true               
-        } else { 
-             false 
-        } 
+        self.scrambled && self.board.is_solved()
     }
 }
 impl epi::App for TemplateApp {
@@ -326,23 +1544,20 @@ impl epi::App for TemplateApp {
     ) {
         env_logger::init();
 
-        let image = image::io::Reader::open(Path::new(BACKGROUND_IMAGE_PATH)).unwrap().decode().unwrap();
-        let image_void_cell = image::io::Reader::open(Path::new(VOID_CELL_CURRENT_IMAGE_PATH)).unwrap().decode().unwrap();
-        let image_winner = image::io::Reader::open(Path::new(VOID_CELL_WINNER_IMAGE_PATH)).unwrap().decode().unwrap();
+        let (image, path) = Self::default_image(BACKGROUND_IMAGE_PATH, BACKGROUND_IMAGE);
+        if let Err(error) = self.set_image(image, path){
+            // a file too small to play on, back to the image built in
+            warn!("Image {} ignored: {}", BACKGROUND_IMAGE_PATH, error);
+            let image = image::load_from_memory(BACKGROUND_IMAGE).expect("the images built in are valid");
+            self.set_image(image, None).expect("the images built in are large enough");
+        }
+
+        let (image_void_cell, _) = Self::default_image(VOID_CELL_CURRENT_IMAGE_PATH, VOID_CELL_CURRENT_IMAGE);
+        let (image_winner, _) = Self::default_image(VOID_CELL_WINNER_IMAGE_PATH, VOID_CELL_WINNER_IMAGE);
 
         let image_void_cell_buffer = image_void_cell.to_rgba8();
-        let image_buffer = image.to_rgba8();
         let image_winner_buffer = image_winner.to_rgba8();
 
-        // background image
-        let cell_width = image.width() as usize / self.sides.num_col;
-        let cell_height = image.height() as usize / self.sides.num_line;
-
-        self.image = egui::ColorImage::from_rgba_unmultiplied(
-            [image.width() as _, image.height() as _], 
-            image_buffer.as_flat_samples().as_slice(),
-        );
-
         // current void_cell image
         let void_cell_width  = image_void_cell.width() as usize;
         let void_cell_height = image_void_cell.height() as usize;
@@ -361,48 +1576,12 @@ impl epi::App for TemplateApp {
             image_winner_buffer.as_flat_samples().as_slice(),
         );
 
-        let mut n : usize = 0;
-        //
-        // A grid is build through a hashmap
-        // the key is "<col>_<line>"
-        // the value is a Cell structure.
-        for line in 1 ..=  self.sides.num_line{
-            for col in 1 ..= self.sides.num_col{
-    
-                let sub_image = image_buffer.view(
-                    (col * cell_width - cell_width).try_into().unwrap(), 
-                    (line * cell_height - cell_height).try_into().unwrap(), 
-                    cell_width.try_into().unwrap(), 
-                    cell_height.try_into().unwrap()).to_image();
-                let size = [sub_image.width() as _, sub_image.height() as _];
-                let pixels = sub_image.as_flat_samples();
- 
-                n += 1;
-
-                let c = Cell{
-                    image: egui::ColorImage::from_rgba_unmultiplied(
-                        size,
-                        pixels.as_slice(),
-
-                     ),
-                    is_void: (line == self.sides.num_line  && col == self.sides.num_col),
-                    rank: n,
-                };
-                if c.is_void {
-                    self.void_cell = TaquinCoord{
-                        col: col, 
-                        line: line,
-                    }
-                }
-                self.cells_map.insert(format!("{}_{}", col, line), c);
-            }
-        }
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         #[cfg(feature = "persistence")]
-        if let Some(storage) = _storage {
-            *self = epi::get_value(storage, epi::APP_KEY).unwrap_or_default()
+        if let Some(state) = _storage.and_then(|storage| epi::get_value(storage, epi::APP_KEY)) {
+            self.restore(state);
         }
     }
 
@@ -410,7 +1589,7 @@ impl epi::App for TemplateApp {
     /// Note that you must enable the `persistence` feature for this to work.
     #[cfg(feature = "persistence")]
     fn save(&mut self, storage: &mut dyn epi::Storage) {
-        epi::set_value(storage, epi::APP_KEY, self);
+        epi::set_value(storage, epi::APP_KEY, &self.persisted_state());
     }
 
     /// Called each time the UI needs repainting, which may be many times per second.
@@ -420,23 +1599,54 @@ impl epi::App for TemplateApp {
 
         // pattern design below
         // let Self { label, 
-        //     scrambled, cells_map, 
-        //     sides, image, 
-        //     void_cell, side_panel_show} = self;
+        //     scrambled, board, 
+        //     cells, image, 
+        //     side_panel_show} = self;
 
         // Examples of how to create different panels and windows.
         // Pick whichever suits you.
         // Tip: a good default choice is to just keep the `CentralPanel`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
 
-        self.add_top_bottom_panel(ctx, frame);
+        self.handle_shortcuts(ctx);
 
-        self.add_central_panel(ctx, frame);
+        self.handle_dropped_files(ctx);
+
+        let max_texture_side = ctx.input().max_texture_side;
+        if max_texture_side != self.max_texture_side{
+            self.max_texture_side = max_texture_side;
+            self.slice_image();
+        }
+
+        self.tick_slide(ctx);
 
+        self.poll_solving(ctx);
+
+        if Self::has_focus(){
+            self.score.tick(ctx.input().unstable_dt as f64);
+        }
+        if self.score.is_running(){
+            // keep the clock ticking on screen
+            ctx.request_repaint();
+        }
+
+        self.add_top_bottom_panel(ctx, frame);
+
+        // the side panel first, the central panel takes the space left
         if self.side_panel_show{
             self.add_side_panel(ctx, frame);
         }
 
+        self.add_central_panel(ctx, frame);
+
+        self.add_playback_window(ctx);
+
+        self.add_summary_window(ctx);
+
+        self.add_paste_window(ctx);
+
+        self.add_file_window(ctx);
+
 
         if false {
             egui::Window::new("Window").show(ctx, |ui| {
@@ -0,0 +1,436 @@
+use super::cell::{
+    TaquinSide,
+    TaquinCoord,
+    Direction,
+    Move,
+};
+
+use rand::{SeedableRng, seq::SliceRandom,};
+use rand_chacha::ChaCha8Rng;
+use log::debug;
+
+/// How far the random walk scramble goes from the solved board.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Difficulty{
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty{
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    /// Number of random moves for a board of `sides`, the larger boards need more.
+    pub fn steps(&self, sides: &TaquinSide) -> usize{
+        let factor = match self{
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 3,
+            Difficulty::Hard => 8,
+            Difficulty::Expert => 20,
+        };
+        factor * sides.num_cells()
+    }
+}
+
+/// The rules of the taquin, without any user interface.
+///
+/// The board holds the permutation of the cells: line by line, the rank
+/// of the cell standing at each position, see `TaquinSide::index`.
+/// The ranks go from 1 to `num_col * num_line` in the reading order of the
+/// solved board, the greatest rank is the void cell.
+#[derive(Debug, Clone)]
+pub struct Board{
+    sides: TaquinSide,
+    cells: Vec<usize>,
+    void_cell: TaquinCoord,
+}
+
+impl Default for Board{
+    fn default() -> Self{
+        Self::new(TaquinSide::default())
+    }
+}
+
+impl Board{
+    /// A solved board, the void cell is the last cell of the last line.
+    pub fn new(sides: TaquinSide) -> Self{
+        Self{
+            void_cell: TaquinCoord{
+                col: sides.num_col,
+                line: sides.num_line,
+            },
+            cells: (1 ..= sides.num_cells()).collect(),
+            sides,
+        }
+    }
+
+    /// A board from its ranks line by line, `None` when they are not
+    /// the ranks from 1 to `num_col * num_line`, each one once.
+    pub fn from_ranks(sides: TaquinSide, cells: Vec<usize>) -> Option<Self>{
+        // no product of the sides which overflows
+        let size = sides.num_col.checked_mul(sides.num_line)?;
+        if cells.len() != size{
+            return None;
+        }
+        let mut seen = vec![false; size];
+        for &rank in cells.iter(){
+            if rank == 0 || rank > size || seen[rank - 1]{
+                return None;
+            }
+            seen[rank - 1] = true;
+        }
+
+        let void_index = cells.iter().position(|&rank| rank == size)?;
+        Some(Self{
+            void_cell: sides.coord(void_index)?,
+            sides,
+            cells,
+        })
+    }
+
+    pub fn sides(&self) -> &TaquinSide{
+        &self.sides
+    }
+
+    pub fn void_cell(&self) -> &TaquinCoord{
+        &self.void_cell
+    }
+
+    /// Rank of the void cell.
+    pub fn void_rank(&self) -> usize{
+        self.sides.num_cells()
+    }
+
+    /// Rank of the cell standing at `coord`, `None` outside of the board.
+    pub fn rank(&self, coord: &TaquinCoord) -> Option<usize>{
+        self.sides.index(coord).map(|index| self.cells[index])
+    }
+
+    pub fn is_void(&self, coord: &TaquinCoord) -> bool{
+        *coord == self.void_cell
+    }
+
+    /// The ranks in the reading order (line by line).
+    pub fn ranks(&self) -> &[usize]{
+        &self.cells
+    }
+
+    /// How many cells a click on `coord` shifts, and which way the void cell goes.
+    /// The click must fall on the line or the column of the void cell
+    /// and cannot be on the void cell, else `Direction::Dontapply` is returned.
+    pub fn direction_to(&self, coord: &TaquinCoord) -> (usize, Direction){
+        match (coord.col, coord.line, self.void_cell.col, self.void_cell.line){
+            // same col click above void_cell
+            (c,l,vcc, vcl) if c == vcc && l < vcl => (vcl - l, Direction::Up),
+            // same col click below void_cell
+            (c,l,vcc, vcl) if c == vcc && l > vcl && l <= self.sides.num_line => (l - vcl, Direction::Down),
+            // same line click before void_cell
+            (c,l,vcc, vcl) if l == vcl && c < vcc => (vcc - c, Direction::Left),
+            // same line click after void_cell
+            (c,l,vcc, vcl) if l == vcl && c > vcc && c <= self.sides.num_col => (c - vcc, Direction::Right),
+            (_,_,_,_) => (0, Direction::Dontapply),
+        }
+    }
+
+    /// The cell next to the void cell in `direction`, `None` on the edge of the board.
+    pub fn neighbour(&self, direction: Direction) -> Option<TaquinCoord>{
+        let c = self.void_cell.col;
+        let l = self.void_cell.line;
+
+        let coord = match direction{
+            Direction::Up    => TaquinCoord{col: c, line: l.checked_sub(1)?},
+            Direction::Down  => TaquinCoord{col: c, line: l + 1},
+            Direction::Left  => TaquinCoord{col: c.checked_sub(1)?, line: l},
+            Direction::Right => TaquinCoord{col: c + 1, line: l},
+            Direction::Dontapply => return None,
+        };
+        self.sides.index(&coord).map(|_| coord)
+    }
+
+    /// Moves the void cell one step in `direction`, the neighbour cell takes its place.
+    /// Returns false when the move would leave the board.
+    pub fn slide(&mut self, direction: Direction) -> bool{
+        let coord = match self.neighbour(direction){
+            Some(coord) => coord,
+            None => return false,
+        };
+        let (index, void_index) = match (self.sides.index(&coord), self.sides.index(&self.void_cell)){
+            (Some(index), Some(void_index)) => (index, void_index),
+            _ => return false,
+        };
+
+        debug!("slide {:?}, void_cell col {}, line {}", direction, coord.col, coord.line);
+
+        self.cells.swap(index, void_index);
+        self.void_cell = coord;
+        true
+    }
+
+    /// Shifts every cell between the void cell and `coord`,
+    /// `coord` becomes the void cell. Returns the move played, if any.
+    pub fn move_to(&mut self, coord: &TaquinCoord) -> Option<Move>{
+        let (delta, direction) = self.direction_to(coord);
+
+        debug!("delta {}, direction {:?}",delta, direction);
+
+        if direction == Direction::Dontapply{
+            return None;
+        }
+        let mv = Move{direction, delta};
+        self.apply(&mv);
+        Some(mv)
+    }
+
+    /// Plays `mv`, slide after slide. Returns false, with the board unchanged,
+    /// when the void cell would leave the board.
+    pub fn apply(&mut self, mv: &Move) -> bool{
+        let (c, l) = (self.void_cell.col, self.void_cell.line);
+        let inside = match mv.direction{
+            // no sum, a delta as large as a usize must not overflow
+            Direction::Up    => l > mv.delta,
+            Direction::Down  => mv.delta <= self.sides.num_line - l,
+            Direction::Left  => c > mv.delta,
+            Direction::Right => mv.delta <= self.sides.num_col - c,
+            Direction::Dontapply => false,
+        };
+        if inside{
+            for _ in 0 .. mv.delta{
+                self.slide(mv.direction);
+            }
+        }
+        inside
+    }
+
+    /// Plays `moves` one after the other. When one of them would leave
+    /// the board, its index is returned and the board is left unchanged.
+    pub fn apply_sequence(&mut self, moves: &[Move]) -> Result<(), usize>{
+        let mut board = self.clone();
+        for (i, mv) in moves.iter().enumerate(){
+            if ! board.apply(mv){
+                return Err(i);
+            }
+        }
+        *self = board;
+        Ok(())
+    }
+
+    /// Puts the cells in a random order which can always be solved.
+    /// The same `seed` always gives the same board.
+    pub fn scramble(&mut self, seed: u64){
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let size = self.void_rank();
+
+        // the ranks are shuffled in the reading order (line by line),
+        // about half of the permutations can't be solved, those ones
+        // are fixed by swapping two cells which are not the void cell,
+        // it flips the parity of the permutation.
+        let mut vect_rank:Vec<usize>= (1 ..= size).collect();
+        vect_rank.shuffle(&mut rng);
+
+        if ! Self::is_solvable_ranks(&self.sides, &vect_rank){
+            let mut not_void = vect_rank.iter().enumerate().filter(|(_, &rank)| rank != size).map(|(i, _)| i);
+            if let (Some(a), Some(b)) = (not_void.next(), not_void.next()){
+                vect_rank.swap(a, b);
+            }
+        }
+
+        // don't forget to update the void_cell
+        let void_index = vect_rank.iter().position(|&rank| rank == size).unwrap_or(0);
+        self.void_cell = self.sides.coord(void_index).unwrap_or_default();
+        self.cells = vect_rank;
+    }
+
+    /// Puts the cells back in order, then plays `steps` random moves,
+    /// never taking back the previous one. The board can always be solved.
+    /// The walk goes on while it falls back on the solved board.
+    /// The same `seed` always gives the same board.
+    pub fn scramble_walk(&mut self, steps: usize, seed: u64){
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        *self = Self::new(self.sides.clone());
+
+        let mut previous = Direction::Dontapply;
+        let mut step = 0;
+        while step < steps || self.is_solved(){
+            step += 1;
+            let directions: Vec<Direction> = Direction::MOVES.into_iter()
+                .filter(|&direction| direction != previous.opposite() && self.neighbour(direction).is_some())
+                .collect();
+            // a board of one line or one column has dead ends
+            let direction = match directions.choose(&mut rng){
+                Some(&direction) => direction,
+                None => break,
+            };
+            self.slide(direction);
+            previous = direction;
+        }
+    }
+
+    /// Every cell is ordered correctly i.e the ranks are sorted.
+    pub fn is_solved(&self) -> bool{
+        self.cells.iter().enumerate().all(|(i, &rank)| rank == i + 1)
+    }
+
+    /// Tells if the board can be put back in order.
+    pub fn is_solvable(&self) -> bool{
+        Self::is_solvable_ranks(&self.sides, &self.cells)
+    }
+
+    //
+    // Tells if the ranks, given in the reading order, can be put back in order.
+    // The void cell has the greatest rank, its home is the last cell of the last line.
+    // Counting the inversions between the cells which are not void:
+    // - the number of columns is odd, the inversions must be even,
+    // - the number of columns is even, each vertical move of the void cell
+    //   flips the parity of the inversions, so the line of the void cell
+    //   counted from the bottom is added, and the sum must be odd.
+    //
+    fn is_solvable_ranks(sides: &TaquinSide, ranks: &[usize]) -> bool{
+        let void_rank = ranks.len();
+        let cells: Vec<usize> = ranks.iter().copied().filter(|&rank| rank != void_rank).collect();
+
+        // the parity of the inversions is the parity of the permutation:
+        // the number of cells less the number of cycles, no need to count
+        // them one by one on a large board
+        let mut visited = vec![false; cells.len()];
+        let mut cycles = 0;
+        for start in 0 .. cells.len(){
+            if visited[start]{
+                continue;
+            }
+            cycles += 1;
+            let mut index = start;
+            while ! visited[index]{
+                visited[index] = true;
+                index = cells[index] - 1;
+            }
+        }
+        let inversions = cells.len() - cycles;
+
+        if sides.num_col % 2 == 1{
+            inversions % 2 == 0
+        }else{
+            let void_index = ranks.iter().position(|&rank| rank == void_rank).unwrap_or(0);
+            let void_line_from_bottom = sides.num_line - void_index / sides.num_col;
+            (inversions + void_line_from_bottom) % 2 == 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use std::collections::HashSet;
+
+    // every position the moves reach from the solved board
+    fn reachable(sides: &TaquinSide) -> HashSet<Vec<usize>>{
+        let start = Board::new(sides.clone());
+        let mut seen = HashSet::from([start.ranks().to_vec()]);
+        let mut stack = vec![start];
+        while let Some(board) = stack.pop(){
+            for direction in Direction::MOVES{
+                let mut next = board.clone();
+                if next.slide(direction) && seen.insert(next.ranks().to_vec()){
+                    stack.push(next);
+                }
+            }
+        }
+        seen
+    }
+
+    // every order of the ranks from 1 to `size`
+    fn permutations(size: usize) -> Vec<Vec<usize>>{
+        if size == 0{
+            return vec![Vec::new()];
+        }
+        let mut all = Vec::new();
+        for shorter in permutations(size - 1){
+            for i in 0 ..= shorter.len(){
+                let mut ranks = shorter.clone();
+                ranks.insert(i, size);
+                all.push(ranks);
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn solvable_means_reachable_for_odd_and_even_widths(){
+        for (num_col, num_line) in [(3, 2), (2, 3), (2, 2), (4, 2)]{
+            let sides = TaquinSide{num_col, num_line};
+            let reachable = reachable(&sides);
+            for ranks in permutations(num_col * num_line){
+                assert_eq!(Board::is_solvable_ranks(&sides, &ranks), reachable.contains(&ranks), "{:?}", ranks);
+            }
+        }
+    }
+
+    #[test]
+    fn scrambles_can_be_solved_for_odd_and_even_widths(){
+        for (num_col, num_line) in [(3, 2), (4, 2)]{
+            let sides = TaquinSide{num_col, num_line};
+            let reachable = reachable(&sides);
+            for seed in 0 .. 50{
+                let mut board = Board::new(sides.clone());
+                board.scramble(seed);
+                assert!(reachable.contains(board.ranks()), "{:?}", board.ranks());
+            }
+        }
+        // is_solvable is checked above on the small boards
+        for (num_col, num_line) in [(5, 5), (4, 4), (7, 6), (6, 7)]{
+            for seed in 0 .. 10{
+                let mut board = Board::new(TaquinSide{num_col, num_line});
+                board.scramble(seed);
+                assert!(board.is_solvable());
+            }
+        }
+    }
+
+    #[test]
+    fn scramble_depends_on_the_seed_only(){
+        let (mut a, mut b, mut c) = (Board::default(), Board::default(), Board::default());
+        a.scramble(42);
+        b.scramble(42);
+        c.scramble(43);
+        assert_eq!(a.ranks(), b.ranks());
+        assert_eq!(a.void_cell(), b.void_cell());
+        assert_ne!(a.ranks(), c.ranks());
+        assert_eq!(a.rank(a.void_cell()), Some(a.void_rank()));
+    }
+
+    #[test]
+    fn scramble_walk_is_never_solved(){
+        for (num_col, num_line) in [(2, 2), (3, 3), (4, 5)]{
+            for seed in 0 .. 20{
+                let mut board = Board::new(TaquinSide{num_col, num_line});
+                board.scramble_walk(1, seed);
+                assert!(! board.is_solved());
+                assert!(board.is_solvable());
+            }
+        }
+    }
+
+    #[test]
+    fn apply_refuses_moves_off_the_board(){
+        let mut board = Board::new(TaquinSide{num_col: 4, num_line: 3});
+        for direction in Direction::MOVES{
+            assert!(! board.apply(&Move{direction, delta: usize::MAX}));
+        }
+        assert!(! board.apply(&Move{direction: Direction::Down, delta: 1}));
+        assert!(! board.apply(&Move{direction: Direction::Left, delta: 4}));
+        assert!(board.is_solved());
+
+        assert!(board.apply(&Move{direction: Direction::Left, delta: 3}));
+        assert!(! board.apply(&Move{direction: Direction::Left, delta: 1}));
+        assert_eq!(board.apply_sequence(&[
+            Move{direction: Direction::Up, delta: 2},
+            Move{direction: Direction::Up, delta: 1},
+        ]), Err(1));
+        assert_eq!(*board.void_cell(), TaquinCoord{col: 1, line: 3});
+    }
+}
@@ -1,4 +1,4 @@
-use eframe::egui::ColorImage;
+use eframe::egui::{pos2, Rect};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Direction{
@@ -9,13 +9,90 @@ pub enum Direction{
     Dontapply,
 }
 
-#[derive(Debug, Clone)]
+impl Direction{
+    /// The four ways the void cell can go.
+    pub const MOVES: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The direction which cancels this one.
+    pub fn opposite(&self) -> Direction{
+        match self{
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Dontapply => Direction::Dontapply,
+        }
+    }
+}
+
+/// A move played on the board: the void cell goes `delta` cells in `direction`,
+/// i.e. a whole run of cells of its line or column is shifted at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Move{
+    pub direction: Direction,
+    pub delta: usize,
+}
+
+impl Move{
+    /// The move which brings the board back.
+    pub fn reverse(&self) -> Move{
+        Move{
+            direction: self.direction.opposite(),
+            delta: self.delta,
+        }
+    }
+}
+
+/// Bounds of a board, for the columns and the lines.
+pub const MIN_SIDE: usize = 2;
+pub const MAX_SIDE: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct TaquinSide{
     pub num_line: usize,
     pub num_col: usize,
 }
 
 
+impl TaquinSide{
+    /// Number of cells of the grid.
+    pub fn num_cells(&self) -> usize{
+        self.num_col * self.num_line
+    }
+
+    /// The columns and the lines are within `MIN_SIDE ..= MAX_SIDE`.
+    pub fn is_valid(&self) -> bool{
+        (MIN_SIDE ..= MAX_SIDE).contains(&self.num_col) && (MIN_SIDE ..= MAX_SIDE).contains(&self.num_line)
+    }
+
+    /// Index of `coord` in a grid stored line by line,
+    /// `None` when `coord` falls outside of the grid (col and line start at 1).
+    pub fn index(&self, coord: &TaquinCoord) -> Option<usize>{
+        if (1 ..= self.num_col).contains(&coord.col) && (1 ..= self.num_line).contains(&coord.line){
+            Some((coord.line - 1) * self.num_col + coord.col - 1)
+        }else{
+            None
+        }
+    }
+
+    /// Coordinates of the cell stored at `index`, `None` past the last cell.
+    pub fn coord(&self, index: usize) -> Option<TaquinCoord>{
+        if index < self.num_cells(){
+            Some(TaquinCoord{
+                line: index / self.num_col + 1,
+                col: index % self.num_col + 1,
+            })
+        }else{
+            None
+        }
+    }
+}
+
 impl Default for TaquinSide{
     fn default() -> Self{
         Self{
@@ -26,40 +103,31 @@ impl Default for TaquinSide{
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct TaquinCoord{
     pub line: usize,
     pub col: usize,
 }
 
-impl Default for TaquinCoord{
-    fn default() -> Self{
-        Self{
-            line: 0,
-            col: 0,
-       }
-    }
-}
-
-#[derive(Clone)]
+/// A cell of the grid, known by its rank: the cell of rank n shows
+/// the n-th part of the image, in the reading order.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Cell{
-    pub image: ColorImage,
-    pub is_void: bool,
     pub rank:usize,
 }
 
 impl Cell {
-}
-
-impl Default for Cell{
-    fn default()-> Self{
-       Self{
-           image: ColorImage::example(),
-           is_void: true,
-           rank: 0,
-       } 
+    /// The part of the image the cell shows, in texture coordinates
+    /// (from 0 to 1), the image being cut by `sides`.
+    pub fn uv(&self, sides: &TaquinSide) -> Rect{
+        let index = self.rank - 1;
+        let (col, line) = ((index % sides.num_col) as f32, (index / sides.num_col) as f32);
+        let (width, height) = (1.0 / sides.num_col as f32, 1.0 / sides.num_line as f32);
+        Rect::from_min_max(
+            pos2(col * width, line * height),
+            pos2((col + 1.0) * width, (line + 1.0) * height),
+        )
     }
-
 }
 
 
@@ -0,0 +1,97 @@
+use super::cell::Move;
+
+/// The moves played on the board, to undo and redo them.
+#[derive(Debug, Clone, Default)]
+pub struct History{
+    done: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl History{
+    /// A history of the moves already played, oldest first.
+    pub fn with_moves(moves: Vec<Move>) -> Self{
+        Self{
+            done: moves,
+            undone: Vec::new(),
+        }
+    }
+
+    /// Records a new move, the moves undone can't be redone anymore.
+    pub fn push(&mut self, mv: Move){
+        self.done.push(mv);
+        self.undone.clear();
+    }
+
+    /// Takes back the last move, returns the move to apply on the board.
+    pub fn undo(&mut self) -> Option<Move>{
+        let mv = self.done.pop()?;
+        self.undone.push(mv);
+        Some(mv.reverse())
+    }
+
+    /// Plays again the last move undone, returns the move to apply on the board.
+    pub fn redo(&mut self) -> Option<Move>{
+        let mv = self.undone.pop()?;
+        self.done.push(mv);
+        Some(mv)
+    }
+
+    pub fn can_undo(&self) -> bool{
+        ! self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool{
+        ! self.undone.is_empty()
+    }
+
+    /// The moves played so far, the oldest first.
+    pub fn moves(&self) -> &[Move]{
+        &self.done
+    }
+
+    pub fn clear(&mut self){
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use super::super::cell::Direction;
+
+    // a shift of `delta` cells, the void cell going `direction`
+    fn shift(direction: Direction, delta: usize) -> Move{
+        Move{direction, delta}
+    }
+
+    #[test]
+    fn a_new_move_drops_the_moves_undone(){
+        let mut history = History::default();
+        history.push(shift(Direction::Left, 1));
+        history.push(shift(Direction::Up, 1));
+        history.undo();
+        assert!(history.can_redo());
+
+        history.push(shift(Direction::Right, 1));
+        assert!(! history.can_redo());
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.moves(), &[shift(Direction::Left, 1), shift(Direction::Right, 1)]);
+    }
+
+    #[test]
+    fn undoes_and_redoes_the_moves_of_several_cells(){
+        let mut history = History::with_moves(vec![shift(Direction::Left, 3)]);
+        history.push(shift(Direction::Down, 2));
+
+        assert_eq!(history.undo(), Some(shift(Direction::Up, 2)), "the whole shift is taken back");
+        assert_eq!(history.undo(), Some(shift(Direction::Right, 3)));
+        assert_eq!(history.undo(), None);
+        assert!(history.moves().is_empty());
+
+        assert_eq!(history.redo(), Some(shift(Direction::Left, 3)));
+        assert_eq!(history.redo(), Some(shift(Direction::Down, 2)));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.moves(), &[shift(Direction::Left, 3), shift(Direction::Down, 2)]);
+    }
+}
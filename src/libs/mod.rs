@@ -1,2 +1,14 @@
+pub mod animation;
 pub mod cell;
+pub mod board;
+pub mod history;
+pub mod notation;
+pub mod playback;
+pub mod position;
+pub mod reduction;
+pub mod save;
+pub mod score;
+pub mod slicing;
+pub mod solver;
+pub mod solving;
 pub mod app;
\ No newline at end of file
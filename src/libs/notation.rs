@@ -0,0 +1,147 @@
+use super::cell::{Direction, Move, TaquinSide};
+
+use std::fmt;
+
+/// Why a text could not be read as a list of moves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseMovesError{
+    /// Not one of the letters U, D, L, R.
+    BadDirection(char),
+    /// A count of 0, or a count larger than the board.
+    BadCount(String),
+}
+
+impl fmt::Display for ParseMovesError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            ParseMovesError::BadDirection(c) => write!(f, "'{}' is not a move, U, D, L or R expected", c),
+            ParseMovesError::BadCount(count) => write!(f, "\"{}\" is not a count of moves on this board", count),
+        }
+    }
+}
+
+impl std::error::Error for ParseMovesError{}
+
+/// A move as a letter, the way the void cell goes, followed by the number
+/// of cells when there are more than one: `U`, `D`, `L`, `R3`.
+impl fmt::Display for Move{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        let letter = match self.direction{
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+            Direction::Dontapply => return Ok(()),
+        };
+        if self.delta == 1{
+            write!(f, "{}", letter)
+        }else{
+            write!(f, "{}{}", letter, self.delta)
+        }
+    }
+}
+
+/// Reads a list of moves such as `R3 U L2D` or `r,r,r,u`: the letters are
+/// the way the void cell goes, a count after a letter repeats it.
+/// Spaces and commas are ignored, the case too.
+///
+/// The moves are for a board of `sides`: a count which could not fit
+/// in a line or a column of it is refused.
+pub fn parse_moves(text: &str, sides: &TaquinSide) -> Result<Vec<Move>, ParseMovesError>{
+    let mut moves = Vec::new();
+    let mut chars = text.chars().filter(|&c| c != ',' && ! c.is_whitespace()).peekable();
+
+    while let Some(c) = chars.next(){
+        let direction = match c.to_ascii_uppercase(){
+            'U' => Direction::Up,
+            'D' => Direction::Down,
+            'L' => Direction::Left,
+            'R' => Direction::Right,
+            _ => return Err(ParseMovesError::BadDirection(c)),
+        };
+
+        let mut count = String::new();
+        while let Some(&digit) = chars.peek(){
+            if ! digit.is_ascii_digit(){
+                break;
+            }
+            count.push(digit);
+            chars.next();
+        }
+        let max = match direction{
+            Direction::Up | Direction::Down => sides.num_line - 1,
+            _ => sides.num_col - 1,
+        };
+        let delta = if count.is_empty(){
+            1
+        }else{
+            match count.parse::<usize>(){
+                Ok(delta) if delta > 0 && delta <= max => delta,
+                _ => return Err(ParseMovesError::BadCount(count)),
+            }
+        };
+        moves.push(Move{direction, delta});
+    }
+    Ok(moves)
+}
+
+/// Writes `moves` separated by spaces, the moves following each other
+/// in the same direction are merged: `R R U` is written `R2 U`.
+pub fn format_moves(moves: &[Move]) -> String{
+    let mut merged: Vec<Move> = Vec::new();
+    for mv in moves{
+        match merged.last_mut(){
+            Some(last) if last.direction == mv.direction => last.delta += mv.delta,
+            _ => merged.push(*mv),
+        }
+    }
+    merged.iter().map(|mv| mv.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// The moves of one cell each, as the solver gives them.
+pub fn moves_from_directions(directions: &[Direction]) -> Vec<Move>{
+    directions.iter().map(|&direction| Move{direction, delta: 1}).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const SIDES: TaquinSide = TaquinSide{num_col: 4, num_line: 3};
+
+    #[test]
+    fn moves_read_back(){
+        let moves = vec![
+            Move{direction: Direction::Right, delta: 3},
+            Move{direction: Direction::Up, delta: 1},
+            Move{direction: Direction::Left, delta: 2},
+            Move{direction: Direction::Down, delta: 2},
+        ];
+        let text = format_moves(&moves);
+        assert_eq!(text, "R3 U L2 D2");
+        assert_eq!(parse_moves(&text, &SIDES), Ok(moves));
+        assert_eq!(parse_moves("", &SIDES), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn the_same_directions_are_merged(){
+        let directions = [Direction::Right, Direction::Right, Direction::Up, Direction::Left];
+        assert_eq!(format_moves(&moves_from_directions(&directions)), "R2 U L");
+        assert_eq!(parse_moves("r,r, r u", &SIDES), parse_moves("R R R U", &SIDES));
+        assert_eq!(parse_moves("rru", &SIDES).map(|moves| format_moves(&moves)), Ok("R2 U".to_owned()));
+    }
+
+    #[test]
+    fn refuses_bad_directions(){
+        assert_eq!(parse_moves("R2 X", &SIDES), Err(ParseMovesError::BadDirection('X')));
+        assert_eq!(parse_moves("3R", &SIDES), Err(ParseMovesError::BadDirection('3')));
+    }
+
+    #[test]
+    fn refuses_counts_larger_than_the_board(){
+        assert_eq!(parse_moves("R3 L3 U2 D2", &SIDES).map(|moves| moves.len()), Ok(4));
+        for text in ["R4", "L4", "U3", "D3", "R0", "U99999999999999999999999"]{
+            assert!(matches!(parse_moves(text, &SIDES), Err(ParseMovesError::BadCount(_))), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::cell::Direction;
+
+/// Plays back a list of moves, one at a time, at a given speed.
+///
+/// The moves are the directions the void cell goes, as the solver gives them.
+/// The playback only tells which move is due, the board is moved by the caller.
+#[derive(Debug, Clone)]
+pub struct Playback{
+    moves: Vec<Direction>,
+    // index of the next move to play
+    next: usize,
+    pub paused: bool,
+    // moves per second
+    pub speed: f32,
+    // time elapsed since the last move, in seconds
+    elapsed: f64,
+}
+
+impl Playback{
+    pub fn new(moves: Vec<Direction>, speed: f32) -> Self{
+        Self{
+            moves,
+            next: 0,
+            paused: false,
+            speed,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Lets the time go by `dt` seconds, returns true when the next move is due.
+    pub fn tick(&mut self, dt: f64) -> bool{
+        if self.paused || self.is_finished(){
+            return false;
+        }
+        self.elapsed += dt;
+        if self.elapsed * self.speed as f64 >= 1.0{
+            self.elapsed = 0.0;
+            true
+        }else{
+            false
+        }
+    }
+
+    /// The next move to play, the playback goes one move forward.
+    pub fn step_forward(&mut self) -> Option<Direction>{
+        let direction = *self.moves.get(self.next)?;
+        self.next += 1;
+        Some(direction)
+    }
+
+    /// The move which takes back the last one played, the playback goes one move back.
+    pub fn step_back(&mut self) -> Option<Direction>{
+        self.next = self.next.checked_sub(1)?;
+        Some(self.moves[self.next].opposite())
+    }
+
+    pub fn is_finished(&self) -> bool{
+        self.next >= self.moves.len()
+    }
+
+    /// Moves played so far.
+    pub fn position(&self) -> usize{
+        self.next
+    }
+
+    pub fn len(&self) -> usize{
+        self.moves.len()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const MOVES: [Direction; 3] = [Direction::Left, Direction::Up, Direction::Right];
+
+    #[test]
+    fn a_move_is_due_at_the_speed(){
+        let mut playback = Playback::new(MOVES.to_vec(), 4.0);
+        assert!(! playback.tick(0.125));
+        assert!(playback.tick(0.125), "4 moves per second, one every 0.25 s");
+        assert!(! playback.tick(0.125), "the time starts over after a move");
+
+        playback.paused = true;
+        assert!(! playback.tick(1.0));
+        playback.paused = false;
+        assert!(playback.tick(0.25));
+    }
+
+    #[test]
+    fn steps_forward_and_back(){
+        let mut playback = Playback::new(MOVES.to_vec(), 1.0);
+        assert_eq!(playback.step_back(), None, "nothing played yet");
+        assert_eq!(playback.step_forward(), Some(Direction::Left));
+        assert_eq!(playback.step_forward(), Some(Direction::Up));
+        assert_eq!(playback.step_back(), Some(Direction::Down), "the last move taken back");
+        assert_eq!(playback.position(), 1);
+        assert_eq!(playback.step_forward(), Some(Direction::Up));
+        assert_eq!(playback.step_forward(), Some(Direction::Right));
+        assert_eq!(playback.position(), playback.len());
+    }
+
+    #[test]
+    fn nothing_is_due_once_finished(){
+        let mut playback = Playback::new(MOVES.to_vec(), 1.0);
+        while playback.step_forward().is_some(){}
+        assert!(playback.is_finished());
+        assert!(! playback.tick(10.0));
+        assert_eq!(playback.step_forward(), None);
+
+        assert!(Playback::new(Vec::new(), 1.0).is_finished());
+    }
+}
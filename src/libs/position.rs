@@ -0,0 +1,191 @@
+use super::board::Board;
+use super::cell::{TaquinSide, MIN_SIDE, MAX_SIDE};
+
+use std::{fmt, str::FromStr,};
+
+/// Why a text could not be read as a position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsePositionError{
+    /// The size is missing or wrong, or the lines are not all the same length.
+    BadDimensions(String),
+    /// Something which is not a cell number.
+    BadTile(String),
+    WrongTileCount{
+        expected: usize,
+        found: usize,
+    },
+    /// A cell number above the last cell.
+    OutOfRange{
+        tile: usize,
+        max: usize,
+    },
+    Duplicate(usize),
+}
+
+impl fmt::Display for ParsePositionError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            ParsePositionError::BadDimensions(reason) => write!(f, "bad dimensions: {}", reason),
+            ParsePositionError::BadTile(tile) => write!(f, "\"{}\" is not a cell number", tile),
+            ParsePositionError::WrongTileCount{expected, found} => write!(f, "{} cells expected, {} found", expected, found),
+            ParsePositionError::OutOfRange{tile, max} => write!(f, "cell {} is out of range, the last one is {}", tile, max),
+            ParsePositionError::Duplicate(tile) => write!(f, "cell {} appears more than once", tile),
+        }
+    }
+}
+
+impl std::error::Error for ParsePositionError{}
+
+/// The text format of a board: `<columns>x<lines>:` then the cells
+/// line by line, separated by commas, the void cell is 0.
+/// For instance the solved 3x2 board is `3x2:1,2,3,4,5,0`.
+///
+/// The alternate form (`{:#}`) writes one line of the board per line of text.
+impl fmt::Display for Board{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        let sides = self.sides();
+        let tiles: Vec<String> = self.ranks().iter()
+            .map(|&rank| if rank == self.void_rank() { 0 } else { rank })
+            .map(|tile| tile.to_string())
+            .collect();
+
+        if f.alternate(){
+            let lines: Vec<String> = tiles.chunks(sides.num_col).map(|line| line.join(" ")).collect();
+            write!(f, "{}", lines.join("\n"))
+        }else{
+            write!(f, "{}x{}:{}", sides.num_col, sides.num_line, tiles.join(","))
+        }
+    }
+}
+
+/// Reads both forms written by `Display`: `3x2:1,2,3,4,5,0`, or the lines
+/// of the board one per line of text, the cells separated by spaces or commas.
+impl FromStr for Board{
+    type Err = ParsePositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>{
+        let s = s.trim();
+
+        let (sides, tiles) = match s.split_once(':'){
+            Some((dimensions, tiles)) => (parse_dimensions(dimensions)?, split_tiles(tiles)),
+            None => {
+                let lines: Vec<Vec<&str>> = s.lines()
+                    .map(split_tiles)
+                    .filter(|line| ! line.is_empty())
+                    .collect();
+                let num_col = lines.first().map(|line| line.len()).unwrap_or(0);
+                if lines.iter().any(|line| line.len() != num_col){
+                    return Err(ParsePositionError::BadDimensions("the lines are not all the same length".to_owned()));
+                }
+                let sides = TaquinSide{
+                    num_col,
+                    num_line: lines.len(),
+                };
+                check_sides(&sides)?;
+                (sides, lines.concat())
+            },
+        };
+
+        let expected = sides.num_cells();
+        if tiles.len() != expected{
+            return Err(ParsePositionError::WrongTileCount{
+                expected,
+                found: tiles.len(),
+            });
+        }
+
+        // the void cell, 0, has the greatest rank on the board
+        let mut seen = vec![false; expected];
+        let mut ranks = Vec::with_capacity(expected);
+        for tile in tiles{
+            let tile: usize = tile.parse().map_err(|_| ParsePositionError::BadTile(tile.to_owned()))?;
+            if tile >= expected{
+                return Err(ParsePositionError::OutOfRange{
+                    tile,
+                    max: expected - 1,
+                });
+            }
+            if seen[tile]{
+                return Err(ParsePositionError::Duplicate(tile));
+            }
+            seen[tile] = true;
+            ranks.push(if tile == 0 { expected } else { tile });
+        }
+
+        Board::from_ranks(sides, ranks).ok_or_else(|| ParsePositionError::BadDimensions("not a board".to_owned()))
+    }
+}
+
+fn split_tiles(tiles: &str) -> Vec<&str>{
+    tiles.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|tile| ! tile.is_empty())
+        .collect()
+}
+
+// "<columns>x<lines>"
+fn parse_dimensions(dimensions: &str) -> Result<TaquinSide, ParsePositionError>{
+    let bad = || ParsePositionError::BadDimensions(format!("\"{}\" is not <columns>x<lines>", dimensions.trim()));
+
+    let (num_col, num_line) = dimensions.trim().split_once(['x', 'X']).ok_or_else(bad)?;
+    let sides = TaquinSide{
+        num_col: num_col.trim().parse().map_err(|_| bad())?,
+        num_line: num_line.trim().parse().map_err(|_| bad())?,
+    };
+    check_sides(&sides)?;
+    Ok(sides)
+}
+
+fn check_sides(sides: &TaquinSide) -> Result<(), ParsePositionError>{
+    if ! sides.is_valid(){
+        return Err(ParsePositionError::BadDimensions(
+            format!("{}x{}, a board has {} to {} columns and lines", sides.num_col, sides.num_line, MIN_SIDE, MAX_SIDE)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn both_forms_read_back(){
+        let mut board = Board::new(TaquinSide{num_col: 4, num_line: 3});
+        board.scramble(3);
+        for text in [board.to_string(), format!("{:#}", board)]{
+            let read: Board = text.parse().unwrap();
+            assert_eq!(read.sides(), board.sides());
+            assert_eq!(read.ranks(), board.ranks());
+            assert_eq!(read.void_cell(), board.void_cell());
+        }
+        assert_eq!(Board::new(TaquinSide{num_col: 3, num_line: 2}).to_string(), "3x2:1,2,3,4,5,0");
+    }
+
+    #[test]
+    fn refuses_a_wrong_tile_count(){
+        assert_eq!("3x2:1,2,3,4,0".parse::<Board>().unwrap_err(), ParsePositionError::WrongTileCount{expected: 6, found: 5});
+        assert_eq!("2x2:1,2,3,0,4".parse::<Board>().unwrap_err(), ParsePositionError::WrongTileCount{expected: 4, found: 5});
+    }
+
+    #[test]
+    fn refuses_duplicates(){
+        assert_eq!("3x2:1,2,3,3,5,0".parse::<Board>().unwrap_err(), ParsePositionError::Duplicate(3));
+        assert_eq!("1 2\n0 0".parse::<Board>().unwrap_err(), ParsePositionError::Duplicate(0));
+    }
+
+    #[test]
+    fn refuses_bad_dimensions(){
+        let wide = format!("{}x2:{}", MAX_SIDE + 1, vec!["1"; 2 * (MAX_SIDE + 1)].join(","));
+        let long_line = vec!["1"; MAX_SIDE + 1].join(" ");
+        let long = format!("{}\n{}", long_line, long_line);
+        for text in ["3:1,2,3,0", "3y2:1,2,3,4,5,0", "ax2:1,2,3,0", "1x4:1,2,3,0", "2x1:1,0", "1 2 3\n4 0", "", "0",
+            "4294967296x4294967296:1", "18446744073709551615x2:1", wide.as_str(), long.as_str()]{
+            assert!(matches!(text.parse::<Board>(), Err(ParsePositionError::BadDimensions(_))), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn refuses_bad_tiles(){
+        assert_eq!("2x2:1,2,3,4".parse::<Board>().unwrap_err(), ParsePositionError::OutOfRange{tile: 4, max: 3});
+        assert_eq!("2x2:1,2,-3,0".parse::<Board>().unwrap_err(), ParsePositionError::BadTile("-3".to_owned()));
+    }
+}
@@ -0,0 +1,392 @@
+use super::board::Board;
+use super::cell::{Direction, TaquinSide};
+use super::solver::Solver;
+
+use std::collections::{HashMap, VecDeque};
+
+/// Solver of a board of any size, quick but far from the shortest solution.
+///
+/// The lines are put in place from the top down to the last three,
+/// then the columns of these from the left down to the last three,
+/// and the corner left, 3 by 3 at most, is solved by `Solver`.
+/// The solution is the list of the directions the void cell goes, like `Solver`.
+pub struct Reduction{
+    num_col: usize,
+    num_line: usize,
+    // ranks line by line, like the board
+    cells: Vec<usize>,
+    // where the cell of each rank stands, by rank - 1
+    places: Vec<usize>,
+    void_index: usize,
+    void_rank: usize,
+    // the cells put in place, nothing moves them any more
+    locked: Vec<bool>,
+    path: Vec<Direction>,
+    solvable: bool,
+    // the breadth-first searches share these: a cell was seen by
+    // the current search when it holds its number
+    seen: Vec<u32>,
+    searches: u32,
+    from: Vec<usize>,
+    queue: VecDeque<usize>,
+}
+
+// A rectangle of the board, the lines and the columns from the first to the last.
+#[derive(Debug, Clone, Copy)]
+struct Window{
+    lines: (usize, usize),
+    cols: (usize, usize),
+}
+
+impl Reduction{
+    pub fn new(board: &Board) -> Self{
+        let sides = board.sides();
+        let cells = board.ranks().to_vec();
+        let mut places = vec![0; cells.len()];
+        for (index, &rank) in cells.iter().enumerate(){
+            places[rank - 1] = index;
+        }
+
+        Self{
+            num_col: sides.num_col,
+            num_line: sides.num_line,
+            void_index: places[board.void_rank() - 1],
+            void_rank: board.void_rank(),
+            locked: vec![false; cells.len()],
+            seen: vec![0; cells.len()],
+            searches: 0,
+            from: vec![0; cells.len()],
+            queue: VecDeque::new(),
+            path: Vec::new(),
+            solvable: board.is_solvable(),
+            cells,
+            places,
+        }
+    }
+
+    /// A list of moves which solves the board, empty when it is already solved.
+    /// `None` when the board can't be solved.
+    pub fn solve(mut self) -> Option<Vec<Direction>>{
+        if ! self.solvable{
+            return None;
+        }
+        let (num_line, num_col) = (self.num_line, self.num_col);
+        if num_line < 2 || num_col < 2{
+            // a single line or column, nothing moves but the void cell
+            self.solve_corner(0, 0)?;
+            return Some(self.path);
+        }
+
+        // the lines, the last two cells of each one go in together
+        let top = num_line.saturating_sub(3);
+        for line in 0 .. top{
+            for col in 0 .. num_col - 2{
+                let index = line * num_col + col;
+                self.place(index + 1, index)?;
+            }
+            let first = line * num_col + num_col - 2;
+            self.place_pair(first, first + 1, first + num_col, Window{
+                lines: (line, line + 2),
+                cols: (num_col.saturating_sub(3), num_col - 1),
+            })?;
+        }
+
+        // the columns of the last lines, the last two cells of each one go in together
+        let left = num_col.saturating_sub(3);
+        for col in 0 .. left{
+            for line in top .. num_line - 2{
+                let index = line * num_col + col;
+                self.place(index + 1, index)?;
+            }
+            let first = (num_line - 2) * num_col + col;
+            self.place_pair(first, first + num_col, first + 2, Window{
+                lines: (top, num_line - 1),
+                cols: (col, col + 2),
+            })?;
+        }
+
+        self.solve_corner(top, left)?;
+        Some(Self::simplify(self.path))
+    }
+
+    //
+    // Moves the void cell, it can't leave the board.
+    //
+    fn slide(&mut self, direction: Direction){
+        let index = self.neighbour(self.void_index, direction)
+            .expect("the void cell stays on the board");
+        let rank = self.cells[index];
+        self.cells.swap(index, self.void_index);
+        self.places[rank - 1] = self.void_index;
+        self.places[self.void_rank - 1] = index;
+        self.void_index = index;
+        self.path.push(direction);
+    }
+
+    fn neighbour(&self, index: usize, direction: Direction) -> Option<usize>{
+        let (line, col) = (index / self.num_col, index % self.num_col);
+        match direction{
+            Direction::Up if line > 0 => Some(index - self.num_col),
+            Direction::Down if line + 1 < self.num_line => Some(index + self.num_col),
+            Direction::Left if col > 0 => Some(index - 1),
+            Direction::Right if col + 1 < self.num_col => Some(index + 1),
+            _ => None,
+        }
+    }
+
+    // The way from `index` to the cell next to it.
+    fn direction(&self, index: usize, next: usize) -> Direction{
+        Direction::MOVES.into_iter()
+            .find(|&direction| self.neighbour(index, direction) == Some(next))
+            .unwrap_or(Direction::Dontapply)
+    }
+
+    //
+    // The shortest way from `start` to a cell `goal` accepts, through
+    // the cells not locked and not `avoid`: the cells gone through,
+    // `start` left out. `None` when there is no way.
+    //
+    fn search(&mut self, start: usize, avoid: Option<usize>, goal: impl Fn(usize) -> bool) -> Option<Vec<usize>>{
+        self.searches += 1;
+        let mut queue = std::mem::take(&mut self.queue);
+        queue.clear();
+        queue.push_back(start);
+        self.seen[start] = self.searches;
+
+        let mut found = None;
+        while let Some(index) = queue.pop_front(){
+            if goal(index){
+                found = Some(index);
+                break;
+            }
+            for direction in Direction::MOVES{
+                if let Some(next) = self.neighbour(index, direction){
+                    if self.seen[next] != self.searches && ! self.locked[next] && Some(next) != avoid{
+                        self.seen[next] = self.searches;
+                        self.from[next] = index;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        self.queue = queue;
+
+        let mut index = found?;
+        let mut way = Vec::new();
+        while index != start{
+            way.push(index);
+            index = self.from[index];
+        }
+        way.reverse();
+        Some(way)
+    }
+
+    //
+    // Walks the void cell to the first cell `goal` accepts, around
+    // the locked cells and `avoid`.
+    //
+    fn walk_void(&mut self, avoid: Option<usize>, goal: impl Fn(usize) -> bool) -> Option<()>{
+        for next in self.search(self.void_index, avoid, goal)?{
+            self.slide(self.direction(self.void_index, next));
+        }
+        Some(())
+    }
+
+    //
+    // Brings the cell of `rank` to `target` and locks it there:
+    // the void cell goes round the cell to push it one step at a time.
+    //
+    fn place(&mut self, rank: usize, target: usize) -> Option<()>{
+        let way = self.search(self.places[rank - 1], None, |index| index == target)?;
+        for next in way{
+            let at = self.places[rank - 1];
+            if self.walk_void(Some(at), |index| index == next).is_none(){
+                // the cell shuts the void cell in a corner, a search
+                // of the moves of both gets it out
+                let window = self.window_around(&[at, self.void_index, target], 2);
+                self.arrange(&[(rank, target)], window)?;
+                break;
+            }
+            self.slide(self.direction(self.void_index, at));
+        }
+        self.locked[target] = true;
+        Some(())
+    }
+
+    //
+    // The last two cells of a line or of a column, `first` and `second`:
+    // the second one can't be put in place after the first one.
+    // Both are brought into `window`, then a search of the moves
+    // of the two and of the void cell puts them in place.
+    //
+    fn place_pair(&mut self, first: usize, second: usize, stage: usize, window: Window) -> Option<()>{
+        self.place(second + 1, first)?;
+        let at = self.places[first];
+        if window.contains(at, self.num_col){
+            self.locked[at] = true;
+        }else{
+            self.place(first + 1, stage)?;
+        }
+
+        let num_col = self.num_col;
+        self.walk_void(None, |index| window.contains(index, num_col))?;
+        self.locked[first] = false;
+        let at = self.places[first];
+        self.locked[at] = false;
+
+        self.arrange(&[(first + 1, first), (second + 1, second)], window)?;
+        self.locked[first] = true;
+        self.locked[second] = true;
+        Some(())
+    }
+
+    //
+    // The shortest moves which bring each rank of `goals` to its cell,
+    // the void cell staying in `window` and off the locked cells.
+    // The void cell and the ranks must be in `window` already.
+    //
+    fn arrange(&mut self, goals: &[(usize, usize)], window: Window) -> Option<()>{
+        // the places of the ranks then of the void cell
+        let mut start: Vec<usize> = goals.iter().map(|&(rank, _)| self.places[rank - 1]).collect();
+        start.push(self.void_index);
+
+        let mut from: HashMap<Vec<usize>, (Vec<usize>, Direction)> = HashMap::new();
+        let mut queue = VecDeque::from([start.clone()]);
+        let mut found = None;
+        while let Some(state) = queue.pop_front(){
+            if goals.iter().zip(state.iter()).all(|(&(_, target), &at)| at == target){
+                found = Some(state);
+                break;
+            }
+            let void_index = state[goals.len()];
+            for direction in Direction::MOVES{
+                let next = match self.neighbour(void_index, direction){
+                    Some(next) if window.contains(next, self.num_col) && ! self.locked[next] => next,
+                    _ => continue,
+                };
+                let moved: Vec<usize> = state.iter()
+                    .map(|&at| if at == next { void_index } else if at == void_index { next } else { at })
+                    .collect();
+                if moved != start && ! from.contains_key(&moved){
+                    from.insert(moved.clone(), (state.clone(), direction));
+                    queue.push_back(moved);
+                }
+            }
+        }
+
+        let mut state = found?;
+        let mut directions = Vec::new();
+        while let Some((previous, direction)) = from.remove(&state){
+            directions.push(direction);
+            state = previous;
+        }
+        for &direction in directions.iter().rev(){
+            self.slide(direction);
+        }
+        Some(())
+    }
+
+    // The rectangle around `indexes`, wider by `margin` cells on each side.
+    fn window_around(&self, indexes: &[usize], margin: usize) -> Window{
+        let lines = indexes.iter().map(|index| index / self.num_col);
+        let cols = indexes.iter().map(|index| index % self.num_col);
+        Window{
+            lines: (
+                lines.clone().min().unwrap_or(0).saturating_sub(margin),
+                (lines.max().unwrap_or(0) + margin).min(self.num_line - 1),
+            ),
+            cols: (
+                cols.clone().min().unwrap_or(0).saturating_sub(margin),
+                (cols.max().unwrap_or(0) + margin).min(self.num_col - 1),
+            ),
+        }
+    }
+
+    //
+    // The cells left, from the line `top` and the column `left` on,
+    // form a small board of their own, solved by the optimal solver.
+    //
+    fn solve_corner(&mut self, top: usize, left: usize) -> Option<()>{
+        let sides = TaquinSide{
+            num_line: self.num_line - top,
+            num_col: self.num_col - left,
+        };
+        let mut ranks = Vec::with_capacity(sides.num_cells());
+        for line in top .. self.num_line{
+            for col in left .. self.num_col{
+                let rank = self.cells[line * self.num_col + col];
+                if rank == self.void_rank{
+                    ranks.push(sides.num_cells());
+                }else{
+                    let (home_line, home_col) = ((rank - 1) / self.num_col, (rank - 1) % self.num_col);
+                    ranks.push((home_line.checked_sub(top)?) * sides.num_col + home_col.checked_sub(left)? + 1);
+                }
+            }
+        }
+
+        let corner = Board::from_ranks(sides, ranks)?;
+        for direction in Solver::new(&corner).solve()?{
+            self.slide(direction);
+        }
+        Some(())
+    }
+
+    // The moves which undo the one before are dropped.
+    fn simplify(path: Vec<Direction>) -> Vec<Direction>{
+        let mut simple: Vec<Direction> = Vec::with_capacity(path.len());
+        for direction in path{
+            if simple.last() == Some(&direction.opposite()){
+                simple.pop();
+            }else{
+                simple.push(direction);
+            }
+        }
+        simple
+    }
+}
+
+impl Window{
+    fn contains(&self, index: usize, num_col: usize) -> bool{
+        let (line, col) = (index / num_col, index % num_col);
+        (self.lines.0 ..= self.lines.1).contains(&line) && (self.cols.0 ..= self.cols.1).contains(&col)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn solves(sides: TaquinSide, seed: u64){
+        let mut board = Board::new(sides.clone());
+        board.scramble(seed);
+        let directions = Reduction::new(&board).solve().expect("a scrambled board can be solved");
+        for direction in directions{
+            assert!(board.slide(direction));
+        }
+        assert!(board.is_solved(), "{:?} seed {}", sides, seed);
+    }
+
+    #[test]
+    fn solves_boards_of_any_shape(){
+        for (num_col, num_line) in [(2, 2), (3, 3), (5, 5), (7, 4), (4, 7), (2, 6), (6, 2), (10, 10)]{
+            for seed in 0 .. 20{
+                solves(TaquinSide{num_col, num_line}, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn a_solved_board_needs_no_move(){
+        let board = Board::new(TaquinSide{num_col: 6, num_line: 5});
+        assert_eq!(Reduction::new(&board).solve(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn refuses_a_board_which_cant_be_solved(){
+        let sides = TaquinSide{num_col: 5, num_line: 5};
+        let mut ranks: Vec<usize> = (1 ..= 25).collect();
+        ranks.swap(0, 1);
+        let board = Board::from_ranks(sides, ranks).unwrap();
+        assert_eq!(Reduction::new(&board).solve(), None);
+    }
+}
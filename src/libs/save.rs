@@ -0,0 +1,257 @@
+use super::board::Board;
+use super::cell::{Move, TaquinSide, TaquinCoord};
+use super::notation::parse_moves;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Version of the save files written, the other versions are refused.
+pub const SAVE_VERSION: u32 = 1;
+
+/// Why a save file was refused.
+#[derive(Debug)]
+pub enum LoadError{
+    Io(std::io::Error),
+    /// Not a save file, or a damaged one.
+    Corrupt(String),
+    Version(u32),
+    /// The game was played on another image.
+    ImageMismatch,
+}
+
+impl fmt::Display for LoadError{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            LoadError::Io(error) => write!(f, "{}", error),
+            LoadError::Corrupt(reason) => write!(f, "corrupt save: {}", reason),
+            LoadError::Version(version) => write!(f, "save version {} is not supported, {} expected", version, SAVE_VERSION),
+            LoadError::ImageMismatch => write!(f, "the game was saved with another image"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError{}
+
+impl From<std::io::Error> for LoadError{
+    fn from(error: std::io::Error) -> Self{
+        LoadError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedCoord{
+    pub col: usize,
+    pub line: usize,
+}
+
+/// A game in progress, as written in a save file (JSON).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame{
+    pub version: u32,
+    pub num_col: usize,
+    pub num_line: usize,
+    /// The ranks line by line, the greatest one is the void cell.
+    pub cells: Vec<usize>,
+    pub void_cell: SavedCoord,
+    /// Where the image came from, when it came from a file.
+    pub image_path: Option<String>,
+    /// `image_hash` of the image the game is played on.
+    pub image_hash: String,
+    /// The moves played, in the move notation, oldest first.
+    pub moves: String,
+    pub move_count: usize,
+    pub tile_count: usize,
+    /// Seconds.
+    pub elapsed: f64,
+    pub seed: Option<u64>,
+}
+
+/// A save file checked and ready to play.
+pub struct LoadedGame{
+    pub board: Board,
+    pub moves: Vec<Move>,
+    pub move_count: usize,
+    pub tile_count: usize,
+    pub elapsed: f64,
+    pub seed: Option<u64>,
+}
+
+impl SavedGame{
+    pub fn to_json(&self) -> String{
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Reads and checks a save file, `image_hash` is the hash of the image
+    /// loaded: a game saved on another image is refused.
+    pub fn from_json(json: &str, image_hash: &str) -> Result<LoadedGame, LoadError>{
+        let saved: SavedGame = serde_json::from_str(json).map_err(|error| LoadError::Corrupt(error.to_string()))?;
+
+        if saved.version != SAVE_VERSION{
+            return Err(LoadError::Version(saved.version));
+        }
+        if saved.image_hash != image_hash{
+            return Err(LoadError::ImageMismatch);
+        }
+
+        let sides = TaquinSide{
+            num_col: saved.num_col,
+            num_line: saved.num_line,
+        };
+        if ! sides.is_valid(){
+            return Err(LoadError::Corrupt(format!("{}x{} is not a board", sides.num_col, sides.num_line)));
+        }
+        let board = Board::from_ranks(sides, saved.cells)
+            .ok_or_else(|| LoadError::Corrupt("the cells are not a permutation".to_owned()))?;
+
+        let void_cell = TaquinCoord{
+            col: saved.void_cell.col,
+            line: saved.void_cell.line,
+        };
+        if *board.void_cell() != void_cell{
+            return Err(LoadError::Corrupt("the void cell is not where the cells put it".to_owned()));
+        }
+
+        // the moves must take the board back to where the game started
+        let moves = parse_moves(&saved.moves, board.sides()).map_err(|error| LoadError::Corrupt(error.to_string()))?;
+        let reversed: Vec<Move> = moves.iter().rev().map(|mv| mv.reverse()).collect();
+        if board.clone().apply_sequence(&reversed).is_err(){
+            return Err(LoadError::Corrupt("the moves don't fit the board".to_owned()));
+        }
+
+        if ! saved.elapsed.is_finite() || saved.elapsed < 0.0{
+            return Err(LoadError::Corrupt(format!("{} is not a time", saved.elapsed)));
+        }
+
+        Ok(LoadedGame{
+            board,
+            moves,
+            move_count: saved.move_count,
+            tile_count: saved.tile_count,
+            elapsed: saved.elapsed,
+            seed: saved.seed,
+        })
+    }
+}
+
+/// A hash of the pixels of an image (FNV-1a, 64 bits), the same on every platform.
+pub fn image_hash(width: usize, height: usize, pixels: &[u8]) -> String{
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in (width as u64).to_le_bytes().iter().chain((height as u64).to_le_bytes().iter()).chain(pixels){
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::libs::cell::{Direction, MAX_SIDE};
+    use crate::libs::notation::format_moves;
+
+    const HASH: &str = "0123456789abcdef";
+
+    // a game on a 4x3 board, one move played since it was scrambled
+    fn saved() -> SavedGame{
+        let mut board = Board::new(TaquinSide{num_col: 4, num_line: 3});
+        board.scramble(5);
+        let mv = Direction::MOVES.into_iter()
+            .map(|direction| Move{direction, delta: 1})
+            .find(|mv| board.clone().apply(mv))
+            .unwrap();
+        board.apply(&mv);
+
+        SavedGame{
+            version: SAVE_VERSION,
+            num_col: 4,
+            num_line: 3,
+            cells: board.ranks().to_vec(),
+            void_cell: SavedCoord{
+                col: board.void_cell().col,
+                line: board.void_cell().line,
+            },
+            image_path: None,
+            image_hash: HASH.to_owned(),
+            moves: format_moves(&[mv]),
+            move_count: 1,
+            tile_count: 1,
+            elapsed: 12.5,
+            seed: Some(5),
+        }
+    }
+
+    fn refused(saved: &SavedGame) -> LoadError{
+        match SavedGame::from_json(&saved.to_json(), HASH){
+            Ok(_) => panic!("{:?} was loaded", saved),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn a_save_reads_back(){
+        let saved = saved();
+        let loaded = SavedGame::from_json(&saved.to_json(), HASH).unwrap();
+        assert_eq!(loaded.board.ranks(), &saved.cells[..]);
+        assert_eq!(format_moves(&loaded.moves), saved.moves);
+        assert_eq!(loaded.elapsed, 12.5);
+        assert_eq!(loaded.seed, Some(5));
+    }
+
+    #[test]
+    fn refuses_what_is_not_a_save(){
+        for json in ["", "{", "[1, 2, 3]", "{\"version\": 1}"]{
+            assert!(matches!(SavedGame::from_json(json, HASH), Err(LoadError::Corrupt(_))), "{:?}", json);
+        }
+    }
+
+    #[test]
+    fn refuses_another_version_or_image(){
+        let newer = SavedGame{version: SAVE_VERSION + 1, ..saved()};
+        assert!(matches!(refused(&newer), LoadError::Version(version) if version == SAVE_VERSION + 1));
+        assert!(matches!(SavedGame::from_json(&saved().to_json(), "fedcba9876543210"), Err(LoadError::ImageMismatch)));
+    }
+
+    #[test]
+    fn refuses_a_damaged_board(){
+        let mut duplicate = saved();
+        duplicate.cells[0] = duplicate.cells[1];
+        let mut short = saved();
+        short.cells.pop();
+        let mut void_cell = saved();
+        void_cell.void_cell.col = void_cell.void_cell.col % 4 + 1;
+        let flat = SavedGame{num_col: 12, num_line: 1, ..saved()};
+        let huge = SavedGame{num_col: 4_294_967_296, num_line: 4_294_967_296, ..saved()};
+        // a solved board, but too wide
+        let wide = SavedGame{
+            num_col: MAX_SIDE + 1,
+            num_line: 2,
+            cells: (1 ..= 2 * (MAX_SIDE + 1)).collect(),
+            void_cell: SavedCoord{col: MAX_SIDE + 1, line: 2},
+            moves: String::new(),
+            ..saved()
+        };
+        let negative = SavedGame{elapsed: -1.0, ..saved()};
+
+        for saved in [duplicate, short, void_cell, flat, huge, wide, negative]{
+            assert!(matches!(refused(&saved), LoadError::Corrupt(_)), "{:?}", saved);
+        }
+    }
+
+    #[test]
+    fn refuses_moves_which_dont_fit(){
+        let bad_notation = SavedGame{moves: "R2 X".to_owned(), ..saved()};
+        let too_long = SavedGame{moves: "R9".to_owned(), ..saved()};
+        // taken back from the current position, the void cell would leave the board
+        let mut off_board = saved();
+        let line = off_board.void_cell.line;
+        off_board.moves = if line == 3{
+            "U".to_owned()
+        }else{
+            format_moves(&[Move{direction: Direction::Down, delta: line}])
+        };
+
+        for saved in [bad_notation, too_long, off_board]{
+            assert!(matches!(refused(&saved), LoadError::Corrupt(_)), "{:?}", saved);
+        }
+    }
+}
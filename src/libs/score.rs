@@ -0,0 +1,176 @@
+use super::cell::Move;
+
+// longest time counted between two frames, in seconds: a longer gap
+// (the process suspended, a frame stalled) is not counted as play time.
+// The app doesn't tick the clock while the page is hidden or without the
+// focus; a native window can't be told apart, eframe 0.17 doesn't tell
+// the app about its focus.
+const MAX_FRAME_GAP: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClockState{
+    // no game yet, the board is not scrambled
+    Idle,
+    // scrambled, waiting for the first move
+    Ready,
+    Running,
+    // the game is won
+    Stopped,
+}
+
+/// The moves played and the time spent on a game,
+/// from the first move after the scramble until the win.
+#[derive(Debug, Clone)]
+pub struct Score{
+    moves: usize,
+    tiles: usize,
+    // seconds
+    elapsed: f64,
+    state: ClockState,
+}
+
+impl Default for Score{
+    fn default() -> Self{
+        Self{
+            moves: 0,
+            tiles: 0,
+            elapsed: 0.0,
+            state: ClockState::Idle,
+        }
+    }
+}
+
+impl Score{
+    /// A new game starts, the clock waits for the first move.
+    pub fn start(&mut self){
+        *self = Self{
+            state: ClockState::Ready,
+            ..Self::default()
+        };
+    }
+
+    /// A game saved goes on, the clock waits for the next move.
+    pub fn resume(&mut self, moves: usize, tiles: usize, elapsed: f64){
+        *self = Self{
+            moves,
+            tiles,
+            elapsed,
+            state: ClockState::Ready,
+        };
+    }
+
+    /// Counts `mv`, whatever the number of cells it shifts, the first move starts the clock.
+    pub fn record(&mut self, mv: &Move){
+        match self.state{
+            ClockState::Ready => self.state = ClockState::Running,
+            ClockState::Running => (),
+            ClockState::Idle | ClockState::Stopped => return,
+        }
+        self.moves += 1;
+        self.tiles += mv.delta;
+    }
+
+    /// Lets the time go by `dt` seconds, the time since the last frame.
+    pub fn tick(&mut self, dt: f64){
+        if self.state == ClockState::Running{
+            self.elapsed += dt.min(MAX_FRAME_GAP);
+        }
+    }
+
+    /// The game is won, the clock stops.
+    pub fn stop(&mut self){
+        if self.state == ClockState::Running{
+            self.state = ClockState::Stopped;
+        }
+    }
+
+    pub fn is_running(&self) -> bool{
+        self.state == ClockState::Running
+    }
+
+    /// Moves played, a shift of several cells counts as one move.
+    pub fn moves(&self) -> usize{
+        self.moves
+    }
+
+    /// Cells moved, one by one.
+    pub fn tiles(&self) -> usize{
+        self.tiles
+    }
+
+    /// Seconds spent.
+    pub fn elapsed(&self) -> f64{
+        self.elapsed
+    }
+
+    /// The time spent as "mm:ss".
+    pub fn time(&self) -> String{
+        let seconds = self.elapsed as u64;
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use super::super::cell::Direction;
+
+    // a shift of `delta` cells
+    fn shift(delta: usize) -> Move{
+        Move{direction: Direction::Left, delta}
+    }
+
+    #[test]
+    fn starts_on_the_first_move(){
+        let mut score = Score::default();
+        score.record(&shift(1));
+        score.tick(0.1);
+        assert_eq!((score.moves(), score.elapsed()), (0, 0.0), "no game before the scramble");
+
+        score.start();
+        score.tick(0.1);
+        assert!(! score.is_running());
+        assert_eq!(score.elapsed(), 0.0, "the clock waits for the first move");
+
+        score.record(&shift(1));
+        score.record(&shift(3));
+        score.tick(0.25);
+        assert!(score.is_running());
+        assert_eq!((score.moves(), score.tiles(), score.elapsed()), (2, 4, 0.25));
+    }
+
+    #[test]
+    fn stops_on_win(){
+        let mut score = Score::default();
+        score.start();
+        score.record(&shift(2));
+        score.tick(0.25);
+        score.stop();
+        score.record(&shift(1));
+        score.tick(0.25);
+        assert!(! score.is_running());
+        assert_eq!((score.moves(), score.tiles(), score.elapsed()), (1, 2, 0.25));
+    }
+
+    #[test]
+    fn resume_waits_for_the_next_move(){
+        let mut score = Score::default();
+        score.resume(7, 12, 65.0);
+        score.tick(0.25);
+        assert_eq!(score.elapsed(), 65.0);
+        assert_eq!(score.time(), "01:05");
+
+        score.record(&shift(2));
+        score.tick(0.25);
+        assert_eq!((score.moves(), score.tiles(), score.elapsed()), (8, 14, 65.25));
+    }
+
+    #[test]
+    fn a_long_gap_between_frames_is_capped(){
+        let mut score = Score::default();
+        score.start();
+        score.record(&shift(1));
+        score.tick(3600.0);
+        assert_eq!(score.elapsed(), MAX_FRAME_GAP);
+    }
+}
@@ -0,0 +1,158 @@
+use super::cell::TaquinSide;
+
+use image::{imageops, Rgba, RgbaImage,};
+
+/// How an image is fitted to the grid before it is sliced,
+/// the cells are squares of the same size whatever the image.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum SliceMode{
+    /// The middle of the image, cut to the shape of the grid.
+    Crop,
+    /// The whole image, in the middle of margins of the padding color.
+    Letterbox,
+    /// The whole image, distorted to the shape of the grid.
+    Stretch,
+}
+
+impl SliceMode{
+    pub const ALL: [SliceMode; 3] = [
+        SliceMode::Crop,
+        SliceMode::Letterbox,
+        SliceMode::Stretch,
+    ];
+
+    /// Fits `image` to a grid of `sides`: the image returned is
+    /// `num_col` by `num_line` squares, `padding` fills the margins
+    /// of the letterbox. An image smaller than the grid in pixels is
+    /// scaled up, the squares are one pixel at least. A larger one is
+    /// scaled down so that no side goes over `max_side` pixels, unless
+    /// the grid itself has more cells than that on a side.
+    pub fn fit(&self, image: &RgbaImage, sides: &TaquinSide, padding: [u8; 4], max_side: u32) -> RgbaImage{
+        let (width, height) = (image.width(), image.height());
+        let (num_col, num_line) = (sides.num_col as u32, sides.num_line as u32);
+        let max_cell = (max_side / num_col.max(num_line)).max(1);
+
+        match self{
+            SliceMode::Crop => {
+                let cell = (width / num_col).min(height / num_line);
+                if cell > 0{
+                    let (crop_width, crop_height) = (cell * num_col, cell * num_line);
+                    let cropped = imageops::crop_imm(
+                        image,
+                        (width - crop_width) / 2,
+                        (height - crop_height) / 2,
+                        crop_width,
+                        crop_height,
+                    ).to_image();
+                    if cell <= max_cell{
+                        return cropped;
+                    }
+                    return imageops::resize(&cropped, max_cell * num_col, max_cell * num_line, imageops::FilterType::Triangle);
+                }
+                // fewer pixels than cells: the largest part of the shape
+                // of the grid, scaled up to one pixel a cell
+                let (crop_width, crop_height) = if width as u64 * num_line as u64 >= height as u64 * num_col as u64{
+                    (((height as u64 * num_col as u64) / num_line as u64).max(1) as u32, height)
+                }else{
+                    (width, ((width as u64 * num_line as u64) / num_col as u64).max(1) as u32)
+                };
+                let cropped = imageops::crop_imm(
+                    image,
+                    (width - crop_width) / 2,
+                    (height - crop_height) / 2,
+                    crop_width,
+                    crop_height,
+                ).to_image();
+                imageops::resize(&cropped, num_col, num_line, imageops::FilterType::Nearest)
+            },
+            SliceMode::Letterbox => {
+                // the image goes down to the largest size within
+                // the largest grid allowed, the margins stay inside too
+                let (max_width, max_height) = (max_cell * num_col, max_cell * num_line);
+                let scaled;
+                let image = if width > max_width || height > max_height{
+                    let scale = (max_width as f64 / width as f64).min(max_height as f64 / height as f64);
+                    let (scaled_width, scaled_height) = (
+                        ((width as f64 * scale) as u32).clamp(1, max_width),
+                        ((height as f64 * scale) as u32).clamp(1, max_height),
+                    );
+                    scaled = imageops::resize(image, scaled_width, scaled_height, imageops::FilterType::Triangle);
+                    &scaled
+                }else{
+                    image
+                };
+                let (width, height) = (image.width(), image.height());
+
+                let cell = ceil_div(width, num_col).max(ceil_div(height, num_line));
+                let mut canvas = RgbaImage::from_pixel(cell * num_col, cell * num_line, Rgba(padding));
+                imageops::overlay(
+                    &mut canvas,
+                    image,
+                    ((cell * num_col - width) / 2) as i64,
+                    ((cell * num_line - height) / 2) as i64,
+                );
+                canvas
+            },
+            SliceMode::Stretch => {
+                // about as many pixels as the image
+                let area = width as f64 * height as f64 / (num_col * num_line) as f64;
+                let cell = (area.sqrt().round() as u32).clamp(1, max_cell);
+                imageops::resize(image, cell * num_col, cell * num_line, imageops::FilterType::Triangle)
+            },
+        }
+    }
+}
+
+fn ceil_div(a: u32, b: u32) -> u32{
+    (a + b - 1) / b
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    // odd sizes, small and large, on square and lopsided grids
+    const IMAGES: [(u32, u32); 5] = [(1, 1), (7, 5), (333, 101), (1201, 899), (99, 1001)];
+    const GRIDS: [(usize, usize); 5] = [(2, 2), (5, 5), (12, 2), (2, 7), (100, 3)];
+    const MAX_SIDE: u32 = 512;
+
+    fn checks(mode: SliceMode){
+        for (width, height) in IMAGES{
+            let image = RgbaImage::from_pixel(width, height, Rgba([200, 100, 50, 255]));
+            for (num_col, num_line) in GRIDS{
+                let fitted = mode.fit(&image, &TaquinSide{num_col, num_line}, [0, 0, 0, 255], MAX_SIDE);
+                let (num_col, num_line) = (num_col as u32, num_line as u32);
+                let cell = fitted.width() / num_col;
+                let case = format!("{:?} {}x{} on {}x{}", mode, width, height, num_col, num_line);
+                assert!(cell >= 1, "{}", case);
+                assert_eq!((fitted.width(), fitted.height()), (cell * num_col, cell * num_line), "{}", case);
+                assert!(fitted.width() <= MAX_SIDE && fitted.height() <= MAX_SIDE, "{}", case);
+            }
+        }
+    }
+
+    #[test]
+    fn crop_gives_square_cells(){
+        checks(SliceMode::Crop);
+    }
+
+    #[test]
+    fn letterbox_gives_square_cells(){
+        checks(SliceMode::Letterbox);
+    }
+
+    #[test]
+    fn stretch_gives_square_cells(){
+        checks(SliceMode::Stretch);
+    }
+
+    #[test]
+    fn letterbox_keeps_the_whole_image(){
+        let image = RgbaImage::from_pixel(400, 300, Rgba([255, 255, 255, 255]));
+        let fitted = SliceMode::Letterbox.fit(&image, &TaquinSide{num_col: 12, num_line: 2}, [0, 0, 0, 255], 4096);
+        // the height of the image fills the grid, the width gets margins
+        assert_eq!((fitted.width(), fitted.height()), (1800, 300));
+        assert_eq!(fitted.get_pixel(900, 150), &Rgba([255, 255, 255, 255]));
+        assert_eq!(fitted.get_pixel(0, 150), &Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,283 @@
+use super::board::Board;
+use super::cell::Direction;
+
+use log::debug;
+
+enum Search{
+    Found,
+    // the smallest cost found above the bound
+    Bound(usize),
+    Aborted,
+}
+
+/// Optimal solver of a board, IDA* guided by the Manhattan distance
+/// plus the linear conflicts.
+///
+/// The solution is the list of the directions the void cell goes, one cell
+/// at a time, the same directions as `Board::slide`.
+pub struct Solver{
+    num_col: usize,
+    num_line: usize,
+    // ranks line by line, like the board
+    cells: Vec<usize>,
+    void_index: usize,
+    void_rank: usize,
+    manhattan: usize,
+    // the extra moves the linear conflicts cost, by line and by column
+    line_conflicts: Vec<usize>,
+    col_conflicts: Vec<usize>,
+    conflicts: usize,
+    path: Vec<Direction>,
+    nodes: u64,
+    max_nodes: u64,
+    solvable: bool,
+}
+
+impl Solver{
+    pub fn new(board: &Board) -> Self{
+        let sides = board.sides();
+        let cells = board.ranks().to_vec();
+        let void_rank = board.void_rank();
+
+        let mut solver = Self{
+            num_col: sides.num_col,
+            num_line: sides.num_line,
+            void_index: cells.iter().position(|&rank| rank == void_rank).unwrap_or(0),
+            cells,
+            void_rank,
+            manhattan: 0,
+            line_conflicts: vec![0; sides.num_line],
+            col_conflicts: vec![0; sides.num_col],
+            conflicts: 0,
+            path: Vec::new(),
+            nodes: 0,
+            max_nodes: u64::MAX,
+            solvable: board.is_solvable(),
+        };
+
+        solver.manhattan = (0 .. solver.cells.len()).map(|index| solver.distance(index)).sum();
+        for line in 0 .. solver.num_line{
+            solver.line_conflicts[line] = solver.line_conflict(line);
+        }
+        for col in 0 .. solver.num_col{
+            solver.col_conflicts[col] = solver.col_conflict(col);
+        }
+        solver.conflicts = solver.line_conflicts.iter().sum::<usize>() + solver.col_conflicts.iter().sum::<usize>();
+        solver
+    }
+
+    /// Limits the number of positions explored, there is no limit by default.
+    /// The larger boards can take far too long to be solved optimally.
+    pub fn with_max_nodes(mut self, max_nodes: u64) -> Self{
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// The shortest list of moves which solves the board, empty when it is already solved.
+    /// `None` when the board can't be solved or the search gave up.
+    pub fn solve(mut self) -> Option<Vec<Direction>>{
+        if ! self.solvable{
+            return None;
+        }
+        let mut bound = self.heuristic();
+        loop{
+            debug!("IDA* bound {}, nodes {}", bound, self.nodes);
+            match self.search(0, bound, Direction::Dontapply){
+                Search::Found => return Some(self.path),
+                Search::Bound(next) if next != usize::MAX => bound = next,
+                // no move left: the board can't be solved
+                Search::Bound(_) => return None,
+                Search::Aborted => return None,
+            }
+        }
+    }
+
+    fn heuristic(&self) -> usize{
+        self.manhattan + self.conflicts
+    }
+
+    fn search(&mut self, g: usize, bound: usize, previous: Direction) -> Search{
+        let h = self.heuristic();
+        if g + h > bound{
+            return Search::Bound(g + h);
+        }
+        if h == 0{
+            return Search::Found;
+        }
+
+        self.nodes += 1;
+        if self.nodes > self.max_nodes{
+            return Search::Aborted;
+        }
+
+        let mut min = usize::MAX;
+        for direction in Direction::MOVES{
+            // going back is never shorter
+            if direction == previous.opposite() || ! self.slide(direction){
+                continue;
+            }
+            self.path.push(direction);
+
+            match self.search(g + 1, bound, direction){
+                Search::Found => return Search::Found,
+                Search::Aborted => return Search::Aborted,
+                Search::Bound(next) => min = min.min(next),
+            }
+
+            self.path.pop();
+            self.slide(direction.opposite());
+        }
+        Search::Bound(min)
+    }
+
+    //
+    // Moves the void cell, the heuristic is updated on the way:
+    // only the cell moved changes its distance, and only the two lines
+    // (or the two columns) it goes between change their conflicts.
+    //
+    fn slide(&mut self, direction: Direction) -> bool{
+        let line = self.void_index / self.num_col;
+        let col = self.void_index % self.num_col;
+
+        let index = match direction{
+            Direction::Up if line > 0 => self.void_index - self.num_col,
+            Direction::Down if line + 1 < self.num_line => self.void_index + self.num_col,
+            Direction::Left if col > 0 => self.void_index - 1,
+            Direction::Right if col + 1 < self.num_col => self.void_index + 1,
+            _ => return false,
+        };
+
+        let old_void_index = self.void_index;
+        self.manhattan -= self.distance(index);
+        self.cells.swap(index, old_void_index);
+        self.void_index = index;
+        self.manhattan += self.distance(old_void_index);
+
+        match direction{
+            Direction::Up | Direction::Down => {
+                for line in [index / self.num_col, old_void_index / self.num_col]{
+                    self.conflicts -= self.line_conflicts[line];
+                    self.line_conflicts[line] = self.line_conflict(line);
+                    self.conflicts += self.line_conflicts[line];
+                }
+            },
+            _ => {
+                for col in [index % self.num_col, old_void_index % self.num_col]{
+                    self.conflicts -= self.col_conflicts[col];
+                    self.col_conflicts[col] = self.col_conflict(col);
+                    self.conflicts += self.col_conflicts[col];
+                }
+            },
+        }
+        true
+    }
+
+    // Manhattan distance of the cell at `index` to its home, nothing for the void cell.
+    fn distance(&self, index: usize) -> usize{
+        let rank = self.cells[index];
+        if rank == self.void_rank{
+            return 0;
+        }
+        let home = rank - 1;
+        let (line, col) = (index / self.num_col, index % self.num_col);
+        let (home_line, home_col) = (home / self.num_col, home % self.num_col);
+        line.abs_diff(home_line) + col.abs_diff(home_col)
+    }
+
+    // The cells of the line which belong to it, but come in the wrong order.
+    fn line_conflict(&self, line: usize) -> usize{
+        let homes: Vec<usize> = (0 .. self.num_col)
+            .map(|col| self.cells[line * self.num_col + col])
+            .filter(|&rank| rank != self.void_rank && (rank - 1) / self.num_col == line)
+            .map(|rank| (rank - 1) % self.num_col)
+            .collect();
+        Self::conflict(&homes)
+    }
+
+    // The cells of the column which belong to it, but come in the wrong order.
+    fn col_conflict(&self, col: usize) -> usize{
+        let homes: Vec<usize> = (0 .. self.num_line)
+            .map(|line| self.cells[line * self.num_col + col])
+            .filter(|&rank| rank != self.void_rank && (rank - 1) % self.num_col == col)
+            .map(|rank| (rank - 1) / self.num_col)
+            .collect();
+        Self::conflict(&homes)
+    }
+
+    //
+    // Every cell which has to leave the line (or the column) to let
+    // the others pass costs two extra moves. The cells which can stay
+    // are the longest increasing run of their homes.
+    //
+    fn conflict(homes: &[usize]) -> usize{
+        let mut longest = vec![1; homes.len()];
+        for i in 0 .. homes.len(){
+            for j in 0 .. i{
+                if homes[j] < homes[i]{
+                    longest[i] = longest[i].max(longest[j] + 1);
+                }
+            }
+        }
+        2 * (homes.len() - longest.iter().max().copied().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::libs::cell::TaquinSide;
+    use std::collections::{HashMap, VecDeque};
+
+    // the fewest moves from each position of the board to the solved one,
+    // a breadth-first search from the solved one
+    fn distances(sides: &TaquinSide) -> HashMap<Vec<usize>, usize>{
+        let start = Board::new(sides.clone());
+        let mut distances = HashMap::from([(start.ranks().to_vec(), 0)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(board) = queue.pop_front(){
+            let distance = distances[board.ranks()];
+            for direction in Direction::MOVES{
+                let mut next = board.clone();
+                if next.slide(direction) && ! distances.contains_key(next.ranks()){
+                    distances.insert(next.ranks().to_vec(), distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    #[test]
+    fn solutions_are_the_shortest_on_3x3(){
+        let sides = TaquinSide{num_col: 3, num_line: 3};
+        let distances = distances(&sides);
+        for seed in 0 .. 30{
+            let mut board = Board::new(sides.clone());
+            board.scramble(seed);
+            let directions = Solver::new(&board).solve().expect("a scrambled board can be solved");
+            assert_eq!(directions.len(), distances[board.ranks()], "{:?}", board.ranks());
+            for direction in directions{
+                assert!(board.slide(direction));
+            }
+            assert!(board.is_solved());
+        }
+    }
+
+    #[test]
+    fn a_solved_board_needs_no_move(){
+        assert_eq!(Solver::new(&Board::default()).solve(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn refuses_a_board_which_cant_be_solved(){
+        let board = Board::from_ranks(TaquinSide{num_col: 3, num_line: 3}, vec![2, 1, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        assert_eq!(Solver::new(&board).solve(), None);
+    }
+
+    #[test]
+    fn gives_up_past_max_nodes(){
+        let mut board = Board::new(TaquinSide{num_col: 4, num_line: 4});
+        board.scramble(1);
+        assert_eq!(Solver::new(&board).with_max_nodes(10).solve(), None);
+    }
+}
@@ -0,0 +1,87 @@
+use super::board::Board;
+use super::cell::Direction;
+use super::reduction::Reduction;
+use super::solver::Solver;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use log::debug;
+
+// the optimal solver is tried up to this number of cells (4x4),
+// the larger boards go straight to the reduction
+const OPTIMAL_MAX_CELLS: usize = 16;
+
+// without threads the search blocks the page, the optimal solver
+// gives up sooner there
+#[cfg(target_arch = "wasm32")]
+const WASM_MAX_NODES: u64 = 200_000;
+
+/// The moves found for a board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution{
+    /// The directions the void cell goes, like `Solver`.
+    pub directions: Vec<Direction>,
+    /// No shorter solution exists.
+    pub optimal: bool,
+}
+
+/// Solves `board`: the optimal solver first on the small boards, as long as it
+/// explores no more than `max_nodes` positions, else the reduction.
+/// `None` when the board can't be solved.
+pub fn solve(board: &Board, max_nodes: u64) -> Option<Solution>{
+    if board.sides().num_cells() <= OPTIMAL_MAX_CELLS{
+        if let Some(directions) = Solver::new(board).with_max_nodes(max_nodes).solve(){
+            return Some(Solution{directions, optimal: true});
+        }
+        debug!("no optimal solution within {} nodes, falling back to the reduction", max_nodes);
+    }
+    Reduction::new(board).solve().map(|directions| Solution{directions, optimal: false})
+}
+
+/// A search of the moves which solve a board, run aside so that
+/// the interface goes on while it lasts.
+///
+/// On the web, where there are no threads, the search is run at once
+/// with a smaller `max_nodes`.
+pub struct Solving{
+    #[cfg(not(target_arch = "wasm32"))]
+    receiver: Receiver<Option<Solution>>,
+    #[cfg(target_arch = "wasm32")]
+    solution: Option<Option<Solution>>,
+}
+
+impl Solving{
+    /// Starts solving a copy of `board`, see `solve`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start(board: &Board, max_nodes: u64) -> Self{
+        let board = board.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            // nobody waits for it any more when the receiver is gone
+            let _ = sender.send(solve(&board, max_nodes));
+        });
+        Self{receiver}
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn start(board: &Board, max_nodes: u64) -> Self{
+        Self{solution: Some(solve(board, max_nodes.min(WASM_MAX_NODES)))}
+    }
+
+    /// `None` as long as the search goes on, then what `solve` returned, once.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn poll(&mut self) -> Option<Option<Solution>>{
+        match self.receiver.try_recv(){
+            Ok(solution) => Some(solution),
+            Err(TryRecvError::Empty) => None,
+            // the search panicked
+            Err(TryRecvError::Disconnected) => Some(None),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn poll(&mut self) -> Option<Option<Solution>>{
+        self.solution.take()
+    }
+}